@@ -0,0 +1,29 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::Context;
+use spike::{extract::Path, routing::get, Router};
+
+// Only the files directly under this directory are read.
+const FILES_DIR: &str = "files";
+
+fn main() -> std::io::Result<()> {
+    let router = Router::new().route(
+        "/files/:name/lines",
+        get(count_lines).constraint("name", is_file_name),
+    );
+
+    spike::serve("0.0.0.0:4444", router)
+}
+
+// Names with separators or starting with a dot, like `..`, get a `404` instead of escaping the
+// directory
+fn is_file_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\'])
+}
+
+// Any error can be propagated with `?`, and is rendered as a `500`
+fn count_lines(Path(name): Path<String>) -> anyhow::Result<String> {
+    let path = PathBuf::from(FILES_DIR).join(&name);
+    let contents = fs::read_to_string(path).with_context(|| format!("reading `{name}`"))?;
+    Ok(contents.lines().count().to_string())
+}
@@ -0,0 +1,13 @@
+use spike::{header_extractor, routing::get, Router, Server};
+
+header_extractor!(TenantId, "x-tenant-id");
+
+fn main() -> std::io::Result<()> {
+    let router = Router::new().route("/", get(hello));
+
+    Server::bind("0.0.0.0:4444").serve(router)
+}
+
+fn hello(TenantId(tenant): TenantId) -> String {
+    format!("Hello tenant {tenant}")
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+
+use serde::Serialize;
+use spike::{
+    extract::RawPathParams,
+    http::{Response, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Body, Json, Router,
+};
+
+fn main() -> std::io::Result<()> {
+    let router = Router::new().route("/users/:id", get(show_user));
+
+    spike::serve("0.0.0.0:4444", router)
+}
+
+#[derive(Serialize)]
+struct User {
+    id: u32,
+    name: String,
+}
+
+fn show_user(params: RawPathParams) -> Result<Json<User>, AppError> {
+    let id = params
+        .get("id")
+        .and_then(|id| id.parse().ok())
+        .ok_or(AppError::NotFound)?;
+
+    Ok(Json(find_user(id)?))
+}
+
+// A database error, coming from some other crate
+#[derive(Debug)]
+enum DbError {
+    NotFound,
+    ConnectionLost,
+}
+
+fn find_user(id: u32) -> Result<User, DbError> {
+    match id {
+        1 => Ok(User {
+            id,
+            name: "Rodrigo".to_owned(),
+        }),
+        42 => Err(DbError::ConnectionLost),
+        _ => Err(DbError::NotFound),
+    }
+}
+
+// The application error, which knows how to render itself as a response
+enum AppError {
+    NotFound,
+    Internal(String),
+}
+
+// Allows using `?` on database calls inside handlers
+impl From<DbError> for AppError {
+    fn from(err: DbError) -> Self {
+        match err {
+            DbError::NotFound => AppError::NotFound,
+            err => AppError::Internal(format!("{err:?}")),
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::NotFound => write!(f, "not found"),
+            AppError::Internal(_) => write!(f, "internal server error"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response<Body> {
+        let status = match self {
+            AppError::NotFound => StatusCode::NOT_FOUND,
+            AppError::Internal(ref cause) => {
+                eprintln!("internal error: {cause}");
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+
+        let body = ErrorBody {
+            error: self.to_string(),
+        };
+
+        (status, Json(body)).into_response()
+    }
+}
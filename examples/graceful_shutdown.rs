@@ -0,0 +1,36 @@
+use std::{thread, time::Duration};
+
+use spike::{routing::get, Router, ServerConfig};
+
+#[cfg(unix)]
+fn main() -> std::io::Result<()> {
+    // Blocks the signals on this thread, and on the ones spawned by it, so they can be waited for.
+    let signals = unsafe {
+        let mut signals = std::mem::zeroed();
+        libc::sigemptyset(&mut signals);
+        libc::sigaddset(&mut signals, libc::SIGTERM);
+        libc::sigaddset(&mut signals, libc::SIGINT);
+        libc::pthread_sigmask(libc::SIG_BLOCK, &signals, std::ptr::null_mut());
+        signals
+    };
+
+    let router = Router::new()
+        .route("/", get(|| "Hello world"))
+        .route("/slow", get(slow));
+
+    ServerConfig::new()
+        .shutdown_grace_period(Duration::from_secs(10))
+        .serve_with_graceful_shutdown("0.0.0.0:4444", router, move || {
+            let mut signal = 0;
+            unsafe { libc::sigwait(&signals, &mut signal) };
+            println!("Received signal {signal}, shutting down");
+        })
+}
+
+#[cfg(not(unix))]
+fn main() {}
+
+fn slow() -> &'static str {
+    thread::sleep(Duration::from_secs(5));
+    "Done"
+}
@@ -0,0 +1,68 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use spike::{
+    http::{Request, Response, StatusCode},
+    layer::Layer,
+    response::IntoResponse,
+    routing::{get, post},
+    Body, Router,
+};
+use touche::server::Service;
+
+fn main() -> std::io::Result<()> {
+    let maintenance = Arc::new(AtomicBool::new(false));
+
+    let admin = Router::new().route(
+        "/maintenance",
+        post({
+            let maintenance = maintenance.clone();
+            move |body: String| {
+                maintenance.store(body.trim() == "on", Ordering::SeqCst);
+                StatusCode::NO_CONTENT
+            }
+        }),
+    );
+
+    let router = Router::new()
+        .route("/", get(|| "Hello world"))
+        .before(MaintenanceLayer(maintenance));
+
+    std::thread::spawn(move || spike::serve("127.0.0.1:4445", admin));
+
+    spike::serve("0.0.0.0:4444", router)
+}
+
+struct MaintenanceLayer(Arc<AtomicBool>);
+
+impl<S> Layer<S> for MaintenanceLayer {
+    type Service = Maintenance<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Maintenance {
+            enabled: self.0.clone(),
+            inner,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Maintenance<S> {
+    enabled: Arc<AtomicBool>,
+    inner: S,
+}
+
+impl<S: Service<Body = Body>> Service for Maintenance<S> {
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        if self.enabled.load(Ordering::SeqCst) {
+            Ok((StatusCode::SERVICE_UNAVAILABLE, "under maintenance").into_response())
+        } else {
+            self.inner.call(req)
+        }
+    }
+}
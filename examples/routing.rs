@@ -32,5 +32,5 @@ fn any_hello(method: Method, body: String) -> impl IntoResponse {
 }
 
 fn world() -> impl IntoResponse {
-    (StatusCode::OK, format!("World"))
+    (StatusCode::OK, "World")
 }
@@ -0,0 +1,11 @@
+use spike::{response::StreamText, routing::get, Router};
+
+fn main() -> std::io::Result<()> {
+    let router = Router::new().route("/numbers", get(numbers));
+
+    spike::serve("0.0.0.0:4444", router)
+}
+
+fn numbers() -> StreamText<impl Iterator<Item = String>> {
+    StreamText((1..=1_000_000).map(|n| format!("{n}\n")))
+}
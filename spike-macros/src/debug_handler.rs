@@ -0,0 +1,102 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{spanned::Spanned, Error, FnArg, ItemFn, ReturnType, Type};
+
+// The handler is emitted untouched, followed by a function that is never called, but asserts the
+// bounds of each argument and of the return type on its own. As these assertions are spanned to
+// the types they check, the compiler errors point right at the offending argument, instead of at
+// the unsatisfied `Handler` bound where the handler is being routed.
+pub(crate) fn expand(handler: ItemFn) -> syn::Result<TokenStream> {
+    let sig = &handler.sig;
+
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "debug_handler doesn't support generic handlers",
+        ));
+    }
+
+    if sig.inputs.len() > 16 {
+        return Err(Error::new(
+            sig.inputs.span(),
+            "handlers can't take more than 16 arguments",
+        ));
+    }
+
+    let last = sig.inputs.len().saturating_sub(1);
+
+    let args = sig
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(idx, arg)| match arg {
+            FnArg::Receiver(receiver) => Err(Error::new(
+                receiver.span(),
+                "handlers must be free functions, without `self`",
+            )),
+            FnArg::Typed(arg) if matches!(*arg.ty, Type::ImplTrait(_)) => Ok(quote! {}),
+            FnArg::Typed(arg) if idx == last => {
+                let ty = &arg.ty;
+                Ok(quote_spanned! {ty.span()=>
+                    {
+                        fn last_argument_must_implement_from_request<T>()
+                        where
+                            T: ::spike::extract::FromRequest,
+                        {
+                        }
+                        last_argument_must_implement_from_request::<#ty>();
+                    }
+                })
+            }
+            FnArg::Typed(arg) => {
+                let ty = &arg.ty;
+                Ok(quote_spanned! {ty.span()=>
+                    {
+                        fn only_the_last_argument_may_consume_the_body<T>()
+                        where
+                            T: ::spike::extract::FromRequestPart,
+                        {
+                        }
+                        only_the_last_argument_may_consume_the_body::<#ty>();
+                    }
+                })
+            }
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) if matches!(**ty, Type::ImplTrait(_)) => quote! {},
+        ReturnType::Type(_, ty) => quote_spanned! {ty.span()=>
+            {
+                fn return_type_must_implement_into_response<T>()
+                where
+                    T: ::spike::response::IntoResponse,
+                {
+                }
+                return_type_must_implement_into_response::<#ty>();
+            }
+        },
+        ReturnType::Default => quote_spanned! {sig.ident.span()=>
+            {
+                fn return_type_must_implement_into_response<T>()
+                where
+                    T: ::spike::response::IntoResponse,
+                {
+                }
+                return_type_must_implement_into_response::<()>();
+            }
+        },
+    };
+
+    let check = format_ident!("__spike_debug_handler_{}", sig.ident);
+
+    Ok(quote! {
+        #handler
+
+        #[allow(dead_code, non_snake_case)]
+        fn #check() {
+            #(#args)*
+            #output
+        }
+    })
+}
@@ -0,0 +1,124 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, ItemFn, Path};
+
+mod debug_handler;
+
+/// Checks that a function can be used as a handler, pointing compile errors at the argument or
+/// return type that doesn't satisfy the handler requirements.
+///
+/// Without it, a handler with an argument that isn't an extractor fails to compile where it is
+/// routed, with an error about the whole function not implementing `Handler`.
+#[proc_macro_attribute]
+pub fn debug_handler(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    let handler = parse_macro_input!(input as ItemFn);
+    debug_handler::expand(handler)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derives `FromRequestPart` for a struct whose fields are all `FromRequestPart` extractors.
+///
+/// Fields are extracted in declaration order, and the first rejection is turned into a response.
+/// A field rejection can be mapped with `#[from_request(map_rejection = path::to::function)]`,
+/// where the function receives the field extractor rejection and returns any `IntoResponse`.
+///
+/// ```no_run
+/// # use spike::{extract::FromRequestPart, http::{HeaderMap, Method, StatusCode}};
+/// # fn bad_headers<R>(_rejection: R) -> StatusCode { StatusCode::BAD_REQUEST }
+/// #[derive(FromRequestPart)]
+/// struct Context {
+///     method: Method,
+///     #[from_request(map_rejection = bad_headers)]
+///     headers: HeaderMap,
+/// }
+/// ```
+#[proc_macro_derive(FromRequestPart, attributes(from_request))]
+pub fn derive_from_request_part(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_request_part(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_from_request_part(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "FromRequestPart can only be derived for structs",
+            ))
+        }
+    };
+
+    let extractions = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| {
+            let ty = &field.ty;
+            let var = format_ident!("__field{}", idx);
+
+            let map_rejection = match map_rejection(&field.attrs)? {
+                Some(map) => quote! {
+                    |rejection| ::spike::response::IntoResponse::into_response(#map(rejection))
+                },
+                None => quote! { ::spike::response::IntoResponse::into_response },
+            };
+
+            Ok(quote! {
+                let #var = <#ty as ::spike::extract::FromRequestPart>::from_request_parts(parts)
+                    .map_err(#map_rejection)?;
+            })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let vars = (0..fields.len()).map(|idx| format_ident!("__field{}", idx));
+
+    let construct = match fields {
+        Fields::Named(fields) => {
+            let names = fields.named.iter().map(|field| &field.ident);
+            quote! { Self { #(#names: #vars),* } }
+        }
+        Fields::Unnamed(_) => quote! { Self(#(#vars),*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::spike::extract::FromRequestPart for #name #ty_generics #where_clause {
+            type Rejection = ::spike::http::Response<::spike::Body>;
+
+            #[allow(unused_variables)]
+            fn from_request_parts(
+                parts: &mut ::spike::http::request::Parts,
+            ) -> ::std::result::Result<Self, Self::Rejection> {
+                #(#extractions)*
+                ::std::result::Result::Ok(#construct)
+            }
+        }
+    })
+}
+
+fn map_rejection(attrs: &[syn::Attribute]) -> syn::Result<Option<Path>> {
+    let mut map = None;
+
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("from_request"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("map_rejection") {
+                map = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown from_request attribute"))
+            }
+        })?;
+    }
+
+    Ok(map)
+}
@@ -0,0 +1,97 @@
+//! Wrappers of [`HttpBody`] implementations.
+
+use std::{
+    io::{self, Read},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use touche::{body::Chunk, HttpBody};
+
+/// Body counting the bytes read from it, e.g. by touche when writing it to the connection, so
+/// layers can account for the size of streaming bodies, whose length isn't known upfront.
+///
+/// The counter is shared, so it can be kept after the body is moved into the response. Trailers
+/// are not counted.
+///
+/// ```no_run
+/// # use spike::{body::CountingBody, http::Response, Body};
+/// # let res = Response::new(Body::from("hello"));
+/// let body = CountingBody::new(res.into_body());
+/// let written = body.counter();
+/// ```
+#[derive(Debug)]
+pub struct CountingBody<B> {
+    inner: B,
+    counter: Arc<AtomicU64>,
+}
+
+impl<B> CountingBody<B> {
+    pub fn new(inner: B) -> Self {
+        Self::with_counter(inner, Arc::default())
+    }
+
+    /// Adds the bytes read to an existing `counter`, like one accumulating every response.
+    pub fn with_counter(inner: B, counter: Arc<AtomicU64>) -> Self {
+        Self { inner, counter }
+    }
+
+    pub fn counter(&self) -> Arc<AtomicU64> {
+        self.counter.clone()
+    }
+}
+
+impl<B: HttpBody> HttpBody for CountingBody<B> {
+    type Reader = CountingReader<B::Reader>;
+    type Chunks = CountingChunks<B::Chunks>;
+
+    fn len(&self) -> Option<u64> {
+        self.inner.len()
+    }
+
+    fn into_reader(self) -> Self::Reader {
+        CountingReader {
+            inner: self.inner.into_reader(),
+            counter: self.counter,
+        }
+    }
+
+    fn into_chunks(self) -> Self::Chunks {
+        CountingChunks {
+            inner: self.inner.into_chunks(),
+            counter: self.counter,
+        }
+    }
+}
+
+pub struct CountingReader<R> {
+    inner: R,
+    counter: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.counter.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+pub struct CountingChunks<I> {
+    inner: I,
+    counter: Arc<AtomicU64>,
+}
+
+impl<I: Iterator<Item = io::Result<Chunk>>> Iterator for CountingChunks<I> {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.inner.next()?;
+        if let Ok(Chunk::Data(data)) = &chunk {
+            self.counter.fetch_add(data.len() as u64, Ordering::Relaxed);
+        }
+        Some(chunk)
+    }
+}
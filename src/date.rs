@@ -0,0 +1,77 @@
+use std::{
+    error::Error,
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use touche::http::HeaderValue;
+
+/// A timestamp as used on HTTP headers like `Last-Modified`, `If-Modified-Since` and
+/// `Retry-After`, with a precision of seconds.
+///
+/// Parses the IMF-fixdate (`Sun, 06 Nov 1994 08:49:37 GMT`), the obsolete RFC 850
+/// (`Sunday, 06-Nov-94 08:49:37 GMT`) and asctime (`Sun Nov  6 08:49:37 1994`) formats, and
+/// always formats as IMF-fixdate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HttpDate(httpdate::HttpDate);
+
+impl HttpDate {
+    /// The current time, or the Unix epoch when the system clock is outside of the range of
+    /// [`HttpDate`].
+    pub fn now() -> Self {
+        Self::try_from(SystemTime::now()).unwrap_or(HttpDate(UNIX_EPOCH.into()))
+    }
+
+    pub fn parse(date: &str) -> Option<Self> {
+        date.trim().parse().ok().map(HttpDate)
+    }
+
+    pub fn format(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+// The first second of the year 10000, which can't be written with four digits.
+const MAX_SECS: u64 = 253_402_300_800;
+
+/// Truncates the time to seconds, failing for times before 1970 or after 9999.
+impl TryFrom<SystemTime> for HttpDate {
+    type Error = DateOutOfRange;
+
+    fn try_from(time: SystemTime) -> Result<Self, Self::Error> {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) if since.as_secs() < MAX_SECS => Ok(HttpDate(time.into())),
+            _ => Err(DateOutOfRange),
+        }
+    }
+}
+
+impl From<HttpDate> for SystemTime {
+    fn from(date: HttpDate) -> Self {
+        date.0.into()
+    }
+}
+
+impl From<HttpDate> for HeaderValue {
+    fn from(date: HttpDate) -> Self {
+        HeaderValue::from_str(&date.format()).unwrap()
+    }
+}
+
+impl fmt::Display for HttpDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// The error converting a [`SystemTime`] outside of the years 1970 to 9999 into an [`HttpDate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateOutOfRange;
+
+impl fmt::Display for DateOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("date out of the range of HTTP dates")
+    }
+}
+
+impl Error for DateOutOfRange {}
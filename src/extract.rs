@@ -1,11 +1,59 @@
-use std::{convert::Infallible, io, str::Utf8Error};
+use std::{
+    convert::Infallible,
+    io::{self, Read},
+    marker::PhantomData,
+    str::Utf8Error,
+    sync::Arc,
+};
 
 use touche::{
-    http::request::Parts as RequestParts, Body, HeaderMap, HttpBody, Method, Request, Response,
-    StatusCode,
+    body::BodyReader,
+    http::{request::Parts as RequestParts, Extensions, HeaderName, HeaderValue, Uri, Version},
+    Body, HeaderMap, HttpBody, Method, Request, Response, StatusCode,
+};
+
+use crate::{
+    handler::{drain_body, drain_reader, BodyClaim, UnreadBody},
+    response::{ErrorDetails, IntoResponse},
 };
 
-use crate::response::IntoResponse;
+mod accept_encoding;
+#[cfg(feature = "json")]
+mod all;
+mod client_ip;
+mod connect_info;
+#[cfg(feature = "json")]
+mod content_typed;
+#[cfg(feature = "file-buffer")]
+mod file_buffered;
+mod path;
+mod query;
+#[cfg(feature = "request-id")]
+mod request_id;
+mod tls_info;
+
+pub use accept_encoding::{AcceptEncoding, Encoding};
+#[cfg(feature = "json")]
+pub use all::{All, AllRejection};
+pub use client_ip::ClientIp;
+pub(crate) use client_ip::{IpCidr, TrustedProxies};
+pub use connect_info::{
+    AddConnectInfo, ConnectInfo, IntoMakeServiceWithConnectInfo, MissingConnectInfo,
+};
+#[cfg(feature = "json")]
+pub use content_typed::{ContentTyped, ContentTypedRejection};
+pub use path::{Path, PathError, PathRejection};
+pub use query::{Query, QueryRejection};
+#[cfg(feature = "request-id")]
+pub use request_id::{MissingRequestId, RequestId};
+pub use tls_info::{AddTlsInfo, IntoMakeServiceWithTlsInfo, MissingTlsInfo, TlsInfo};
+
+#[cfg(feature = "json")]
+pub use crate::json::{Json, JsonRejection, StrictJson};
+#[cfg(feature = "file-buffer")]
+pub use file_buffered::{FileBufferedBody, FileBufferedBodyRejection};
+#[cfg(feature = "macros")]
+pub use spike_macros::FromRequestPart;
 
 pub trait FromRequest: Sized {
     type Rejection: IntoResponse;
@@ -19,6 +67,44 @@ pub trait FromRequestPart: Sized {
     fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection>;
 }
 
+/// Common interface of the rejections of the built-in extractors, so they can all be rendered in
+/// the same way, e.g. by a layer formatting errors as JSON.
+///
+/// Their [`IntoResponse`] implementations respond with this status and body text, or the detailed
+/// one on routers with [`error_details`](crate::Router::error_details) enabled.
+pub trait Rejection {
+    fn status(&self) -> StatusCode;
+
+    /// The message describing the rejection.
+    fn body_text(&self) -> String;
+
+    /// The message along with details about the cause, like deserialization errors, when there
+    /// are any.
+    fn detailed_body_text(&self) -> Option<String> {
+        None
+    }
+}
+
+// Responds with the status and body text of the `rejection`, keeping the detailed text for routers
+// showing error details.
+pub(crate) fn rejection_response(rejection: &impl Rejection) -> Response<Body> {
+    let mut res = (rejection.status(), rejection.body_text()).into_response();
+    if let Some(details) = rejection.detailed_body_text() {
+        res.extensions_mut().insert(ErrorDetails(details));
+    }
+    res
+}
+
+impl Rejection for Infallible {
+    fn status(&self) -> StatusCode {
+        match *self {}
+    }
+
+    fn body_text(&self) -> String {
+        match *self {}
+    }
+}
+
 impl FromRequestPart for Method {
     type Rejection = Infallible;
 
@@ -27,6 +113,24 @@ impl FromRequestPart for Method {
     }
 }
 
+impl FromRequestPart for Uri {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(parts.uri.clone())
+    }
+}
+
+/// Routing doesn't depend on the HTTP version, so handlers serving several versions can use this to
+/// branch on it.
+impl FromRequestPart for Version {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(parts.version)
+    }
+}
+
 impl FromRequestPart for HeaderMap {
     type Rejection = Infallible;
 
@@ -35,17 +139,308 @@ impl FromRequestPart for HeaderMap {
     }
 }
 
+/// Names the header read by the [`HeaderRef`] extractor.
+///
+/// ```no_run
+/// # use spike::{extract::HeaderKey, http::{header, HeaderName}};
+/// struct UserAgent;
+///
+/// impl HeaderKey for UserAgent {
+///     const NAME: HeaderName = header::USER_AGENT;
+/// }
+/// ```
+pub trait HeaderKey {
+    const NAME: HeaderName;
+}
+
+/// The value of a single request header, named by `K`. Cheaper than extracting the whole
+/// [`HeaderMap`], which clones every header.
+pub struct HeaderRef<K> {
+    value: Option<HeaderValue>,
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<K> HeaderRef<K> {
+    pub fn value(&self) -> Option<&HeaderValue> {
+        self.value.as_ref()
+    }
+
+    pub fn to_str(&self) -> Option<&str> {
+        self.value.as_ref()?.to_str().ok()
+    }
+
+    pub fn into_inner(self) -> Option<HeaderValue> {
+        self.value
+    }
+}
+
+impl<K: HeaderKey> FromRequestPart for HeaderRef<K> {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(HeaderRef {
+            value: parts.headers.get(K::NAME).cloned(),
+            _key: PhantomData,
+        })
+    }
+}
+
+/// The URI of the request as received by the server, before any prefix was stripped from the path
+/// by [`Router::nest_service`](crate::Router::nest_service).
+#[derive(Debug, Clone)]
+pub struct OriginalUri(pub Uri);
+
+impl FromRequestPart for OriginalUri {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<OriginalUri>()
+            .cloned()
+            .unwrap_or_else(|| OriginalUri(parts.uri.clone())))
+    }
+}
+
+/// The path template of the route that matched the request, like `/users/:id`.
+///
+/// Set by the [`Router`](crate::Router) when a route matches, so it is available to the handlers
+/// and to the layers added with [`Router::route_layer`](crate::Router::route_layer), but not to
+/// the [`before`](crate::Router::before) ones. For services nested with
+/// [`Router::nest_service`](crate::Router::nest_service), it is the template matched by the
+/// innermost router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedPath(pub(crate) Arc<str>);
+
+impl MatchedPath {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromRequestPart for MatchedPath {
+    type Rejection = MissingMatchedPath;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<MatchedPath>()
+            .cloned()
+            .ok_or(MissingMatchedPath)
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingMatchedPath;
+
+impl Rejection for MissingMatchedPath {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "missing matched path".to_owned()
+    }
+}
+
+impl IntoResponse for MissingMatchedPath {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
+
+/// The form of the request target, as sent on the request line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestTarget {
+    /// An absolute path with an optional query, like `/users?page=2`, sent on most requests.
+    Origin,
+    /// A full URI, like `http://example.com/users`, sent to proxies.
+    Absolute,
+    /// A host and port, like `example.com:443`, only sent on `CONNECT` requests.
+    Authority,
+    /// The `*` of `OPTIONS *` requests, targeting the server as a whole.
+    Asterisk,
+}
+
+impl FromRequestPart for RequestTarget {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        let uri = &parts.uri;
+        Ok(if uri.scheme().is_some() {
+            RequestTarget::Absolute
+        } else if uri.authority().is_some() || parts.method == Method::CONNECT {
+            RequestTarget::Authority
+        } else if uri.path() == "*" && parts.method == Method::OPTIONS {
+            RequestTarget::Asterisk
+        } else {
+            RequestTarget::Origin
+        })
+    }
+}
+
+/// A value of type `T` from the request extensions, usually inserted by a middleware, like the
+/// authenticated user.
+///
+/// As a response part, it inserts the value on the response extensions instead.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Extension<T>(pub T);
+
+impl<T: Clone + Send + Sync + 'static> FromRequestPart for Extension<T> {
+    type Rejection = MissingExtension;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<T>()
+            .cloned()
+            .map(Extension)
+            .ok_or(MissingExtension(std::any::type_name::<T>()))
+    }
+}
+
+/// Rejection of the [`Extension`] extractor, when the request has no extension of that type.
+///
+/// The missing type is named on the detailed body text.
+#[derive(Debug)]
+pub struct MissingExtension(&'static str);
+
+impl Rejection for MissingExtension {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "missing request extension".to_owned()
+    }
+
+    fn detailed_body_text(&self) -> Option<String> {
+        Some(format!("missing request extension `{}`", self.0))
+    }
+}
+
+impl IntoResponse for MissingExtension {
+    fn into_response(self) -> Response<Body> {
+        rejection_response(&self)
+    }
+}
+
+/// Rejection of the extractors declared with [`header_extractor!`](crate::header_extractor).
+#[derive(Debug)]
+pub enum HeaderRejection {
+    Missing(&'static str),
+    Invalid(&'static str),
+}
+
+impl Rejection for HeaderRejection {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn body_text(&self) -> String {
+        match self {
+            HeaderRejection::Missing(name) => format!("missing `{name}` header"),
+            HeaderRejection::Invalid(name) => format!("invalid `{name}` header"),
+        }
+    }
+}
+
+impl IntoResponse for HeaderRejection {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
+
+/// The query string of the request, exactly as it was received, without any decoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawQuery(pub Option<String>);
+
+impl FromRequestPart for RawQuery {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(RawQuery(parts.uri.query().map(str::to_owned)))
+    }
+}
+
+/// The path parameters captured by the router, as raw strings, still percent-encoded, in the order
+/// they appear on the route.
+#[derive(Debug, Clone, Default)]
+pub struct RawPathParams(Vec<(String, String)>);
+
+impl RawPathParams {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find_map(|(key, value)| (key == name).then_some(value))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl FromRequestPart for RawPathParams {
+    type Rejection = MissingPathParams;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Vec<(String, String)>>()
+            .cloned()
+            .map(RawPathParams)
+            .ok_or(MissingPathParams)
+    }
+}
+
+/// Rejection of path extractors used on handlers that were not called by a
+/// [`Router`](crate::Router).
+#[derive(Debug)]
+pub struct MissingPathParams;
+
+impl Rejection for MissingPathParams {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "missing path params".to_owned()
+    }
+}
+
+impl IntoResponse for MissingPathParams {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
+
 pub enum StringRejection {
     Io(io::Error),
     InvalidUtf8(Utf8Error),
 }
 
+impl Rejection for StringRejection {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "error reading body".to_owned()
+    }
+}
+
 impl IntoResponse for StringRejection {
     fn into_response(self) -> Response<Body> {
-        Response::builder()
-            .status(StatusCode::INTERNAL_SERVER_ERROR)
-            .body(Body::from("error reading body"))
-            .unwrap()
+        (self.status(), self.body_text()).into_response()
     }
 }
 
@@ -56,11 +451,19 @@ where
     type Rejection = <Self as FromRequestPart>::Rejection;
 
     fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
-        let (mut parts, _body) = req.into_parts();
+        let (mut parts, body) = req.into_parts();
+        if !drain_body(body) {
+            BodyClaim::close(&parts.extensions);
+            if let Some(unread) = parts.extensions.get::<UnreadBody>() {
+                unread.mark();
+            }
+        }
         Self::from_request_parts(&mut parts)
     }
 }
 
+/// Reads the body until it ends, so chunked bodies, which have no `Content-Length`, are read
+/// whole. The same goes for the other extractors buffering the body, like `Bytes`.
 impl FromRequest for String {
     type Rejection = StringRejection;
 
@@ -73,6 +476,104 @@ impl FromRequest for String {
     }
 }
 
+/// The request body as text, replacing invalid UTF-8 sequences with `U+FFFD` instead of rejecting
+/// the request like `String` does.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LossyString(pub String);
+
+impl FromRequest for LossyString {
+    type Rejection = StringRejection;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        let body = req.into_body().into_bytes().map_err(StringRejection::Io)?;
+        Ok(LossyString(String::from_utf8_lossy(&body).into_owned()))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl FromRequest for bytes::Bytes {
+    type Rejection = BytesRejection;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        let body = req.into_body().into_bytes().map_err(BytesRejection)?;
+        Ok(body.into())
+    }
+}
+
+#[cfg(feature = "bytes")]
+#[derive(Debug)]
+pub struct BytesRejection(pub io::Error);
+
+#[cfg(feature = "bytes")]
+impl Rejection for BytesRejection {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "error reading body".to_owned()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl IntoResponse for BytesRejection {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
+
+/// Reads the request body incrementally, without buffering it into memory.
+///
+/// As it consumes the body, it must be the last extractor of a handler, and can't be combined with
+/// other body extractors like `String`. What the handler leaves unread of a chunked body is
+/// drained once it returns, up to a limit past which the connection is closed after responding.
+pub struct BodyStream {
+    reader: BodyReader,
+    unread: Option<UnreadBody>,
+}
+
+impl Read for BodyStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl FromRequest for BodyStream {
+    type Rejection = Infallible;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        let unread = req.extensions().get::<UnreadBody>().cloned();
+        Ok(BodyStream {
+            reader: req.into_body().into_reader(),
+            unread,
+        })
+    }
+}
+
+impl Drop for BodyStream {
+    fn drop(&mut self) {
+        if let Some(unread) = &self.unread {
+            if !drain_reader(&mut self.reader) {
+                unread.mark();
+            }
+        }
+    }
+}
+
+/// All the request extensions, to inspect what the middlewares inserted there.
+///
+/// As `Extensions` can't be cloned, the map is moved out of the request, leaving it empty for the
+/// extractors after this one, so it should be the last part extractor of a handler. The body is
+/// left for the body extractor. Use [`Extension`] to extract a single value among other
+/// extractors.
+impl FromRequestPart for Extensions {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(std::mem::take(&mut parts.extensions))
+    }
+}
+
 macro_rules! impl_from_request {
     ([$($ty:ident),*], $last:ident) => {
         #[allow(non_snake_case, unused_mut)]
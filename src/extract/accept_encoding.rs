@@ -0,0 +1,160 @@
+use std::convert::Infallible;
+
+use touche::{header, http::request::Parts as RequestParts};
+
+use crate::extract::FromRequestPart;
+
+/// The content codings understood by [`AcceptEncoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Identity,
+    Gzip,
+    Deflate,
+    Br,
+}
+
+impl Encoding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Encoding::Identity => "identity",
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+            Encoding::Br => "br",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "identity" => Some(Encoding::Identity),
+            "gzip" | "x-gzip" => Some(Encoding::Gzip),
+            "deflate" => Some(Encoding::Deflate),
+            "br" => Some(Encoding::Br),
+            _ => None,
+        }
+    }
+}
+
+/// The content codings accepted by the client, parsed from the `Accept-Encoding` header.
+///
+/// `identity` is acceptable unless excluded with `identity;q=0`, or with `*;q=0` without an
+/// `identity` entry. Other codings are only acceptable when listed, or matched by `*`, with a non
+/// zero quality, so a request without the header accepts `identity` only. Unknown codings are
+/// ignored.
+#[derive(Debug, Clone, Default)]
+pub struct AcceptEncoding {
+    // Qualities in thousandths, ordered from the most preferred.
+    encodings: Vec<(Encoding, u16)>,
+    any: Option<u16>,
+}
+
+impl AcceptEncoding {
+    /// Returns the codings listed on the header, most preferred first, without the ones whose
+    /// quality is zero.
+    pub fn iter(&self) -> impl Iterator<Item = Encoding> + '_ {
+        self.encodings
+            .iter()
+            .filter(|(_, quality)| *quality > 0)
+            .map(|(encoding, _)| *encoding)
+    }
+
+    pub fn accepts(&self, encoding: Encoding) -> bool {
+        self.rank(encoding).0 > 0
+    }
+
+    /// Returns the acceptable coding of the `available` ones that the client prefers. Codings
+    /// listed on the header win over the ones with the same quality matched by `*`, and other ties
+    /// are resolved by the order of `available`.
+    pub fn preferred(&self, available: &[Encoding]) -> Option<Encoding> {
+        available
+            .iter()
+            .copied()
+            .filter(|encoding| self.accepts(*encoding))
+            .fold(None, |best: Option<Encoding>, encoding| match best {
+                Some(best) if self.rank(best) >= self.rank(encoding) => Some(best),
+                _ => Some(encoding),
+            })
+    }
+
+    // The quality of the coding, and whether it is listed on the header.
+    fn rank(&self, encoding: Encoding) -> (u16, bool) {
+        match self
+            .encodings
+            .iter()
+            .find(|(listed, _)| *listed == encoding)
+        {
+            Some((_, quality)) => (*quality, true),
+            None => match (self.any, encoding) {
+                (Some(quality), _) => (quality, false),
+                // Acceptable, but less preferred than any listed coding.
+                (None, Encoding::Identity) => (1, false),
+                (None, _) => (0, false),
+            },
+        }
+    }
+
+    fn parse<'a>(values: impl Iterator<Item = &'a str>) -> Self {
+        let mut accept = AcceptEncoding::default();
+
+        for item in values.flat_map(|value| value.split(',')) {
+            let mut params = item.split(';');
+            let name = params
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase();
+            let quality = params
+                .filter_map(|param| param.split_once('='))
+                .find(|(key, _)| key.trim().eq_ignore_ascii_case("q"))
+                .map(|(_, quality)| parse_quality(quality.trim()))
+                .unwrap_or(Some(1000));
+
+            let Some(quality) = quality else {
+                continue;
+            };
+
+            if name == "*" {
+                accept.any = Some(quality);
+            } else if let Some(encoding) = Encoding::parse(&name) {
+                if accept
+                    .encodings
+                    .iter()
+                    .all(|(listed, _)| *listed != encoding)
+                {
+                    accept.encodings.push((encoding, quality));
+                }
+            }
+        }
+
+        accept.encodings.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+        accept
+    }
+}
+
+// Parses a quality value, from `0` to `1` with up to three decimals, into thousandths.
+fn parse_quality(quality: &str) -> Option<u16> {
+    let (int, decimals) = quality.split_once('.').unwrap_or((quality, ""));
+    if decimals.len() > 3 || !decimals.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let decimals = format!("{decimals:0<3}").parse::<u16>().ok()?;
+    match int {
+        "0" => Some(decimals),
+        "1" if decimals == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+impl FromRequestPart for AcceptEncoding {
+    type Rejection = Infallible;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        Ok(AcceptEncoding::parse(
+            parts
+                .headers
+                .get_all(header::ACCEPT_ENCODING)
+                .iter()
+                .filter_map(|value| value.to_str().ok()),
+        ))
+    }
+}
@@ -0,0 +1,180 @@
+use std::{collections::BTreeMap, io};
+
+use serde::{
+    de::{self, value::MapDeserializer, DeserializeOwned, IntoDeserializer},
+    Deserializer,
+};
+use serde_json::Value;
+use touche::{Body, HttpBody, Request, Response, StatusCode};
+
+use crate::{
+    extract::{
+        path::{decoded_params, ValueDeserializer},
+        rejection_response, FromRequest, PathRejection, Rejection,
+    },
+    json::has_json_content_type,
+    response::IntoResponse,
+};
+
+/// Path params, query string and JSON body of the request, merged and deserialized into `T`.
+///
+/// Fields present on more than one source are taken from the body first, then the query string,
+/// then the path. The body is only merged on requests with a JSON content type (`application/json`
+/// or `application/*+json`) and must be a JSON object, other bodies are ignored.
+///
+/// As it consumes the body, it must be the last extractor of a handler.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct All<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequest for All<T> {
+    type Rejection = AllRejection;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        let (parts, body) = req.into_parts();
+
+        let mut fields = BTreeMap::new();
+
+        let params = match decoded_params(&parts) {
+            Err(PathRejection::MissingPathParams) => Vec::new(),
+            params => params.map_err(AllRejection::Path)?,
+        };
+        for (key, value) in params {
+            fields.insert(key, Field::Text(value));
+        }
+
+        let query = parts.uri.query().unwrap_or_default();
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            fields.insert(key.into_owned(), Field::Text(value.into_owned()));
+        }
+
+        if has_json_content_type(&parts.headers) {
+            let body = body.into_bytes().map_err(AllRejection::Io)?;
+            if !body.is_empty() {
+                let body = serde_json::from_slice::<serde_json::Map<String, Value>>(&body)
+                    .map_err(AllRejection::InvalidJsonBody)?;
+                for (key, value) in body {
+                    fields.insert(key, Field::Json(value));
+                }
+            }
+        }
+
+        let de = MapDeserializer::new(fields.iter().map(|(key, value)| {
+            (
+                key.as_str(),
+                FieldDeserializer {
+                    key: key.as_str(),
+                    field: value,
+                },
+            )
+        }));
+
+        serde_path_to_error::deserialize(de)
+            .map(All)
+            .map_err(AllRejection::InvalidFields)
+    }
+}
+
+/// Rejection of the [`All`] extractor.
+///
+/// On routers with [`error_details`](crate::Router::error_details) enabled, deserialization errors are described on
+/// the response body.
+#[derive(Debug)]
+pub enum AllRejection {
+    Path(PathRejection),
+    Io(io::Error),
+    /// The body has a JSON content type, but is not a JSON object.
+    InvalidJsonBody(serde_json::Error),
+    InvalidFields(serde_path_to_error::Error<serde_json::Error>),
+}
+
+impl Rejection for AllRejection {
+    fn status(&self) -> StatusCode {
+        match self {
+            AllRejection::Path(rejection) => rejection.status(),
+            AllRejection::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AllRejection::InvalidJsonBody(_) | AllRejection::InvalidFields(_) => {
+                StatusCode::BAD_REQUEST
+            }
+        }
+    }
+
+    fn body_text(&self) -> String {
+        match self {
+            AllRejection::Path(rejection) => rejection.body_text(),
+            AllRejection::Io(_) => "error reading body".to_owned(),
+            AllRejection::InvalidJsonBody(_) => "invalid JSON body".to_owned(),
+            AllRejection::InvalidFields(_) => "invalid request fields".to_owned(),
+        }
+    }
+
+    fn detailed_body_text(&self) -> Option<String> {
+        match self {
+            AllRejection::Path(rejection) => rejection.detailed_body_text(),
+            AllRejection::Io(_) => None,
+            AllRejection::InvalidJsonBody(err) => Some(format!("invalid JSON body: {err}")),
+            AllRejection::InvalidFields(err) => Some(format!("invalid request fields: {err}")),
+        }
+    }
+}
+
+impl IntoResponse for AllRejection {
+    fn into_response(self) -> Response<Body> {
+        rejection_response(&self)
+    }
+}
+
+enum Field {
+    Text(String),
+    Json(Value),
+}
+
+struct FieldDeserializer<'de> {
+    key: &'de str,
+    field: &'de Field,
+}
+
+impl<'de> IntoDeserializer<'de, serde_json::Error> for FieldDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! forward_to_field {
+    ($($method:ident $(($($arg:ident: $ty:ty),*))?)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(
+                self,
+                $($($arg: $ty,)*)?
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                match self.field {
+                    Field::Text(value) => {
+                        ValueDeserializer::new(self.key, value).$method($($($arg,)*)? visitor)
+                    }
+                    Field::Json(value) => value.$method($($($arg,)*)? visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for FieldDeserializer<'de> {
+    type Error = serde_json::Error;
+
+    forward_to_field! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32
+        deserialize_u64 deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char
+        deserialize_str deserialize_string deserialize_bytes deserialize_byte_buf
+        deserialize_option deserialize_unit deserialize_seq deserialize_map deserialize_identifier
+        deserialize_ignored_any
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+}
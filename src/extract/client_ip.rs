@@ -0,0 +1,154 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+
+use touche::{header, http::request::Parts as RequestParts, HeaderMap};
+
+use crate::extract::{ConnectInfo, FromRequestPart, MissingConnectInfo};
+
+/// The address of the client that originated the request.
+///
+/// When the connection comes from one of the [trusted proxies](crate::Router::trusted_proxies),
+/// the `Forwarded` (or, in its absence, `X-Forwarded-For`) header is walked from the closest hop
+/// backwards, skipping trusted proxies, and the first untrusted address is returned. Requests from
+/// untrusted peers always resolve to the socket address, so the headers can't be spoofed.
+///
+/// Requires the [`ConnectInfo`] of the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl FromRequestPart for ClientIp {
+    type Rejection = MissingConnectInfo;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        let ConnectInfo(peer) = ConnectInfo::from_request_parts(parts)?;
+        let peer = peer.ip().to_canonical();
+
+        let proxies = match parts.extensions.get::<TrustedProxies>() {
+            Some(proxies) if proxies.contains(peer) => proxies,
+            _ => return Ok(ClientIp(peer)),
+        };
+
+        let mut client = peer;
+        for hop in forwarded_for(&parts.headers).into_iter().rev() {
+            match hop {
+                Some(ip) => {
+                    client = ip;
+                    if !proxies.contains(ip) {
+                        break;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        Ok(ClientIp(client))
+    }
+}
+
+fn forwarded_for(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    let forwarded = headers
+        .get_all(header::FORWARDED)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|element| {
+            element.split(';').find_map(|pair| {
+                let (key, value) = pair.split_once('=')?;
+                key.trim()
+                    .eq_ignore_ascii_case("for")
+                    .then(|| parse_node(value.trim().trim_matches('"')))
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if !forwarded.is_empty() {
+        return forwarded;
+    }
+
+    headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|node| parse_node(node.trim()))
+        .collect()
+}
+
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let ip = if let Some(rest) = node.strip_prefix('[') {
+        rest.split_once(']')?.0.parse().ok()?
+    } else {
+        node.parse::<IpAddr>()
+            .ok()
+            .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))?
+    };
+    Some(IpAddr::to_canonical(&ip))
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TrustedProxies(Arc<Vec<IpCidr>>);
+
+impl TrustedProxies {
+    pub(crate) fn new(proxies: Vec<IpCidr>) -> Self {
+        Self(Arc::new(proxies))
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        self.0.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct IpCidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid CIDR `{s}`");
+
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+
+        let addr = addr
+            .parse::<IpAddr>()
+            .map_err(|_| invalid())?
+            .to_canonical();
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| invalid())?,
+            None => max,
+        };
+
+        if prefix > max {
+            return Err(invalid());
+        }
+
+        Ok(Self { addr, prefix })
+    }
+}
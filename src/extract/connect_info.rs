@@ -0,0 +1,90 @@
+use std::net::SocketAddr;
+
+use touche::{
+    http::request::Parts as RequestParts,
+    server::{MakeService, Service},
+    Body, Connection, Request, Response, StatusCode,
+};
+
+use crate::{
+    extract::{FromRequestPart, Rejection},
+    response::IntoResponse,
+};
+
+/// The remote address of the connection that sent the request.
+///
+/// Requires the service to be served with
+/// [`Router::into_make_service_with_connect_info`](crate::Router::into_make_service_with_connect_info).
+#[derive(Debug, Clone, Copy)]
+pub struct ConnectInfo(pub SocketAddr);
+
+impl FromRequestPart for ConnectInfo {
+    type Rejection = MissingConnectInfo;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<ConnectInfo>()
+            .copied()
+            .ok_or(MissingConnectInfo)
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingConnectInfo;
+
+impl Rejection for MissingConnectInfo {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "missing connection info".to_owned()
+    }
+}
+
+impl IntoResponse for MissingConnectInfo {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
+
+#[derive(Clone)]
+pub struct IntoMakeServiceWithConnectInfo<S>(pub(crate) S);
+
+impl<S> MakeService for IntoMakeServiceWithConnectInfo<S>
+where
+    S: Service + Clone + Send,
+{
+    type Service = AddConnectInfo<S>;
+    type Error = std::convert::Infallible;
+
+    fn call(&self, conn: &Connection) -> Result<Self::Service, Self::Error> {
+        Ok(AddConnectInfo {
+            svc: self.0.clone(),
+            addr: conn.peer_addr(),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct AddConnectInfo<S> {
+    svc: S,
+    addr: Option<SocketAddr>,
+}
+
+impl<S: Service> Service for AddConnectInfo<S> {
+    type Body = S::Body;
+    type Error = S::Error;
+
+    fn call(&self, mut req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        if let Some(addr) = self.addr {
+            req.extensions_mut().insert(ConnectInfo(addr));
+        }
+        self.svc.call(req)
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.svc.should_continue(req)
+    }
+}
@@ -0,0 +1,99 @@
+use std::io;
+
+use serde::de::DeserializeOwned;
+use touche::{header, Body, HttpBody, Request, Response, StatusCode};
+
+use crate::{
+    extract::{rejection_response, FromRequest, Rejection},
+    json::{has_json_content_type, Json, JsonRejection},
+    response::IntoResponse,
+};
+
+/// Request body deserialized into `T` as JSON or as an URL encoded form, according to its content
+/// type, so a handler can serve both API clients and HTML forms.
+///
+/// Requests with other content types, or without one, are rejected with a
+/// `415 Unsupported Media Type`.
+///
+/// As it consumes the body, it must be the last extractor of a handler.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ContentTyped<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequest for ContentTyped<T> {
+    type Rejection = ContentTypedRejection;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        if has_json_content_type(req.headers()) {
+            let Json(value) = Json::from_request(req).map_err(ContentTypedRejection::Json)?;
+            return Ok(ContentTyped(value));
+        }
+
+        if !has_form_content_type(&req) {
+            return Err(ContentTypedRejection::UnsupportedContentType);
+        }
+
+        let body = req
+            .into_body()
+            .into_bytes()
+            .map_err(ContentTypedRejection::Io)?;
+        serde_urlencoded::from_bytes(&body)
+            .map(ContentTyped)
+            .map_err(ContentTypedRejection::InvalidForm)
+    }
+}
+
+fn has_form_content_type(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .and_then(|content_type| content_type.split(';').next())
+        .is_some_and(|mime| {
+            mime.trim()
+                .eq_ignore_ascii_case("application/x-www-form-urlencoded")
+        })
+}
+
+/// Rejection of the [`ContentTyped`] extractor.
+#[derive(Debug)]
+pub enum ContentTypedRejection {
+    UnsupportedContentType,
+    Json(JsonRejection),
+    Io(io::Error),
+    InvalidForm(serde_urlencoded::de::Error),
+}
+
+impl Rejection for ContentTypedRejection {
+    fn status(&self) -> StatusCode {
+        match self {
+            ContentTypedRejection::UnsupportedContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ContentTypedRejection::Json(rejection) => rejection.status(),
+            ContentTypedRejection::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ContentTypedRejection::InvalidForm(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+
+    fn body_text(&self) -> String {
+        match self {
+            ContentTypedRejection::UnsupportedContentType => {
+                "expected request with `Content-Type: application/json` or `Content-Type: application/x-www-form-urlencoded`".to_owned()
+            }
+            ContentTypedRejection::Json(rejection) => rejection.body_text(),
+            ContentTypedRejection::Io(_) => "error reading body".to_owned(),
+            ContentTypedRejection::InvalidForm(_) => "invalid form body".to_owned(),
+        }
+    }
+
+    fn detailed_body_text(&self) -> Option<String> {
+        match self {
+            ContentTypedRejection::Json(rejection) => rejection.detailed_body_text(),
+            ContentTypedRejection::InvalidForm(err) => Some(format!("invalid form body: {err}")),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for ContentTypedRejection {
+    fn into_response(self) -> Response<Body> {
+        rejection_response(&self)
+    }
+}
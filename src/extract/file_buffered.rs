@@ -0,0 +1,107 @@
+use std::{
+    fs::File,
+    io::{self, Cursor, Read, Seek, SeekFrom, Write},
+};
+
+use touche::{Body, HttpBody, Request, Response, StatusCode};
+
+use crate::{
+    extract::{FromRequest, Rejection},
+    response::IntoResponse,
+};
+
+/// The request body, buffered in memory up to `THRESHOLD` bytes, and spilled to a temporary file
+/// when it gets bigger than that. Defaults to a 1 MiB threshold.
+///
+/// The temporary file is removed as soon as the extractor, or the [`File`] taken out of it, is
+/// dropped.
+pub enum FileBufferedBody<const THRESHOLD: usize = 1_048_576> {
+    Memory(Cursor<Vec<u8>>),
+    File(File),
+}
+
+impl<const THRESHOLD: usize> FileBufferedBody<THRESHOLD> {
+    /// Returns the body as a [`File`], creating the temporary file if it was kept in memory.
+    pub fn into_file(self) -> io::Result<File> {
+        match self {
+            FileBufferedBody::Memory(buf) => {
+                let mut file = tempfile::tempfile()?;
+                file.write_all(buf.get_ref())?;
+                file.seek(SeekFrom::Start(0))?;
+                Ok(file)
+            }
+            FileBufferedBody::File(file) => Ok(file),
+        }
+    }
+
+    pub fn is_file(&self) -> bool {
+        matches!(self, FileBufferedBody::File(_))
+    }
+}
+
+impl<const THRESHOLD: usize> Read for FileBufferedBody<THRESHOLD> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            FileBufferedBody::Memory(cursor) => cursor.read(buf),
+            FileBufferedBody::File(file) => file.read(buf),
+        }
+    }
+}
+
+impl<const THRESHOLD: usize> Seek for FileBufferedBody<THRESHOLD> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            FileBufferedBody::Memory(cursor) => cursor.seek(pos),
+            FileBufferedBody::File(file) => file.seek(pos),
+        }
+    }
+}
+
+impl<const THRESHOLD: usize> FromRequest for FileBufferedBody<THRESHOLD> {
+    type Rejection = FileBufferedBodyRejection;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        let mut body = req.into_body().into_reader();
+
+        let mut buf = Vec::new();
+        (&mut body)
+            .take(THRESHOLD as u64 + 1)
+            .read_to_end(&mut buf)
+            .map_err(FileBufferedBodyRejection)?;
+
+        if buf.len() <= THRESHOLD {
+            return Ok(FileBufferedBody::Memory(Cursor::new(buf)));
+        }
+
+        let mut spill = || -> io::Result<File> {
+            let mut file = tempfile::tempfile()?;
+            file.write_all(&buf)?;
+            io::copy(&mut body, &mut file)?;
+            file.seek(SeekFrom::Start(0))?;
+            Ok(file)
+        };
+
+        Ok(FileBufferedBody::File(
+            spill().map_err(FileBufferedBodyRejection)?,
+        ))
+    }
+}
+
+#[derive(Debug)]
+pub struct FileBufferedBodyRejection(pub io::Error);
+
+impl Rejection for FileBufferedBodyRejection {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "error buffering body".to_owned()
+    }
+}
+
+impl IntoResponse for FileBufferedBodyRejection {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
@@ -0,0 +1,341 @@
+use std::{fmt, marker::PhantomData};
+
+use percent_encoding::percent_decode_str;
+use serde::{
+    de::{self, value::BorrowedStrDeserializer, DeserializeOwned, IntoDeserializer},
+    forward_to_deserialize_any, Deserializer,
+};
+use touche::{http::request::Parts as RequestParts, Body, Response, StatusCode};
+
+use crate::{
+    extract::{rejection_response, FromRequestPart, MissingPathParams, Rejection},
+    response::IntoResponse,
+};
+
+/// Path parameters captured by the router, percent-decoded and deserialized into `T`.
+///
+/// A single param can be extracted as a plain value (`Path<u32>`), several of them as a tuple, in
+/// the order they appear on the route, or as a struct, by name. `Path<Option<T>>` yields `None`
+/// when the route has no params, which is useful with
+/// [`Router::with_trailing_param_optional`](crate::Router::with_trailing_param_optional).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Path<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequestPart for Path<T> {
+    type Rejection = PathRejection;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        let params = decoded_params(parts)?;
+
+        T::deserialize(PathDeserializer { params: &params })
+            .map(Path)
+            .map_err(PathRejection::InvalidPathParams)
+    }
+}
+
+pub(crate) fn decoded_params(parts: &RequestParts) -> Result<Vec<(String, String)>, PathRejection> {
+    parts
+        .extensions
+        .get::<Vec<(String, String)>>()
+        .ok_or(PathRejection::MissingPathParams)?
+        .iter()
+        .map(
+            |(key, value)| match percent_decode_str(value).decode_utf8() {
+                Ok(value) => Ok((key.clone(), value.into_owned())),
+                Err(_) => Err(PathRejection::InvalidUtf8InPathParam(key.clone())),
+            },
+        )
+        .collect()
+}
+
+/// Rejection of the [`Path`] extractor.
+///
+/// On routers with [`error_details`](crate::Router::error_details) enabled, the reason why the params couldn't be
+/// deserialized is described on the response body.
+#[derive(Debug)]
+pub enum PathRejection {
+    MissingPathParams,
+    /// The named param is not valid UTF-8 once percent-decoded.
+    InvalidUtf8InPathParam(String),
+    InvalidPathParams(PathError),
+}
+
+impl Rejection for PathRejection {
+    fn status(&self) -> StatusCode {
+        match self {
+            PathRejection::MissingPathParams => MissingPathParams.status(),
+            PathRejection::InvalidUtf8InPathParam(_) | PathRejection::InvalidPathParams(_) => {
+                StatusCode::BAD_REQUEST
+            }
+        }
+    }
+
+    fn body_text(&self) -> String {
+        match self {
+            PathRejection::MissingPathParams => MissingPathParams.body_text(),
+            PathRejection::InvalidUtf8InPathParam(key) => {
+                format!("invalid UTF-8 on `{key}` path param")
+            }
+            PathRejection::InvalidPathParams(_) => "invalid path params".to_owned(),
+        }
+    }
+
+    fn detailed_body_text(&self) -> Option<String> {
+        match self {
+            PathRejection::InvalidPathParams(err) => Some(format!("invalid path params: {err}")),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for PathRejection {
+    fn into_response(self) -> Response<Body> {
+        rejection_response(&self)
+    }
+}
+
+#[derive(Debug)]
+pub struct PathError(String);
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PathError {}
+
+impl de::Error for PathError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        PathError(msg.to_string())
+    }
+}
+
+struct PathDeserializer<'de> {
+    params: &'de [(String, String)],
+}
+
+impl<'de> PathDeserializer<'de> {
+    fn single(&self) -> Result<ValueDeserializer<'de, PathError>, PathError> {
+        match self.params {
+            [(key, value)] => Ok(ValueDeserializer::new(key, value)),
+            params => Err(PathError(format!(
+                "expected 1 path param, found {}",
+                params.len()
+            ))),
+        }
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for PathDeserializer<'de> {
+    type Error = PathError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.params {
+            [_] => self.single()?.deserialize_any(visitor),
+            _ => self.deserialize_map(visitor),
+        }
+    }
+
+    forward_to_single! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_unit
+        deserialize_identifier
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.params.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            self.params
+                .iter()
+                .map(|(key, value)| ValueDeserializer::new(key, value)),
+        ))
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        if self.params.len() != len {
+            return Err(PathError(format!(
+                "expected {len} path params, found {}",
+                self.params.len()
+            )));
+        }
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(de::value::MapDeserializer::new(self.params.iter().map(
+            |(key, value)| {
+                (
+                    BorrowedStrDeserializer::new(key.as_str()),
+                    ValueDeserializer::new(key, value),
+                )
+            },
+        )))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+}
+
+// Deserializes the value of a single named param, parsing it when a number or bool is expected.
+pub(crate) struct ValueDeserializer<'de, E> {
+    key: &'de str,
+    value: &'de str,
+    _error: PhantomData<E>,
+}
+
+impl<'de, E> ValueDeserializer<'de, E> {
+    pub(crate) fn new(key: &'de str, value: &'de str) -> Self {
+        ValueDeserializer {
+            key,
+            value,
+            _error: PhantomData,
+        }
+    }
+}
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let value = self.value.parse().map_err(|_| {
+                    E::custom(format!(
+                        "cannot parse `{}` param with value `{}`",
+                        self.key, self.value
+                    ))
+                })?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de, E: de::Error> Deserializer<'de> for ValueDeserializer<'de, E> {
+    type Error = E;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        IntoDeserializer::<E>::into_deserializer(self.value)
+            .deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+        ignored_any
+    }
+}
+
+impl<'de, E: de::Error> IntoDeserializer<'de, E> for ValueDeserializer<'de, E> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
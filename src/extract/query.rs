@@ -0,0 +1,114 @@
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserializer,
+};
+use touche::{http::request::Parts as RequestParts, Body, Response, StatusCode};
+
+use crate::{
+    extract::{rejection_response, FromRequestPart, Rejection},
+    response::IntoResponse,
+};
+
+/// Query string of the request, deserialized into `T`.
+///
+/// `Query<Option<T>>` yields `None` only when the request has no query string at all, so a
+/// request to `/path?` gets `Some` with `T` deserialized from an empty query. Fully dynamic queries
+/// can be extracted as `Query<HashMap<String, String>>`, or as `Query<Vec<(String, String)>>` to
+/// keep repeated keys, like `a=1&a=2`, and the order of the pairs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequestPart for Query<T> {
+    type Rejection = QueryRejection;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        T::deserialize(QueryDeserializer {
+            query: parts.uri.query(),
+        })
+        .map(Query)
+        .map_err(QueryRejection::InvalidQuery)
+    }
+}
+
+/// Rejection of the [`Query`] extractor.
+///
+/// On routers with [`error_details`](crate::Router::error_details) enabled, the reason why the query couldn't be
+/// deserialized is described on the response body.
+#[derive(Debug)]
+pub enum QueryRejection {
+    InvalidQuery(serde_urlencoded::de::Error),
+}
+
+impl Rejection for QueryRejection {
+    fn status(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn body_text(&self) -> String {
+        match self {
+            QueryRejection::InvalidQuery(_) => "invalid query string".to_owned(),
+        }
+    }
+
+    fn detailed_body_text(&self) -> Option<String> {
+        match self {
+            QueryRejection::InvalidQuery(err) => Some(format!("invalid query string: {err}")),
+        }
+    }
+}
+
+impl IntoResponse for QueryRejection {
+    fn into_response(self) -> Response<Body> {
+        rejection_response(&self)
+    }
+}
+
+struct QueryDeserializer<'de> {
+    query: Option<&'de str>,
+}
+
+impl<'de> QueryDeserializer<'de> {
+    fn urlencoded(self) -> serde_urlencoded::Deserializer<'de> {
+        let query = self.query.unwrap_or_default();
+        serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()))
+    }
+}
+
+macro_rules! forward_to_urlencoded {
+    ($($method:ident $(($($arg:ident: $ty:ty),*))?)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(
+                self,
+                $($($arg: $ty,)*)?
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                self.urlencoded().$method($($($arg,)*)? visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for QueryDeserializer<'de> {
+    type Error = serde_urlencoded::de::Error;
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.query {
+            Some(_) => visitor.visit_some(self.urlencoded()),
+            None => visitor.visit_none(),
+        }
+    }
+
+    forward_to_urlencoded! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_unit deserialize_seq deserialize_map
+        deserialize_identifier deserialize_ignored_any
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+}
@@ -0,0 +1,58 @@
+use std::{fmt, sync::Arc};
+
+use touche::{http::request::Parts as RequestParts, Body, Response, StatusCode};
+
+use crate::{
+    extract::{FromRequestPart, Rejection},
+    response::IntoResponse,
+};
+
+/// The identifier of the request, to correlate its log entries.
+///
+/// Requires the [`SetRequestId`](crate::layer::SetRequestId) layer, and rejects requests it didn't
+/// run on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(pub(crate) Arc<str>);
+
+impl RequestId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequestPart for RequestId {
+    type Rejection = MissingRequestId;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<RequestId>()
+            .cloned()
+            .ok_or(MissingRequestId)
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingRequestId;
+
+impl Rejection for MissingRequestId {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "missing request id".to_owned()
+    }
+}
+
+impl IntoResponse for MissingRequestId {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
@@ -0,0 +1,100 @@
+use touche::{
+    http::request::Parts as RequestParts,
+    server::{MakeService, Service},
+    Body, Connection, Request, Response, StatusCode,
+};
+
+use crate::{
+    extract::{FromRequestPart, Rejection},
+    response::IntoResponse,
+};
+
+/// Details of the TLS session of the connection that sent the request.
+///
+/// Requires the service to be served with
+/// [`Router::into_make_service_with_tls_info`](crate::Router::into_make_service_with_tls_info),
+/// and rejects requests received on plaintext connections.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsInfo {
+    /// The host name sent by the client with SNI.
+    pub server_name: Option<String>,
+    /// The protocol negotiated with ALPN, like `h2` or `http/1.1`.
+    pub alpn_protocol: Option<Vec<u8>>,
+    /// The subject common name of the certificate presented by the client.
+    pub peer_common_name: Option<String>,
+}
+
+impl FromRequestPart for TlsInfo {
+    type Rejection = MissingTlsInfo;
+
+    fn from_request_parts(parts: &mut RequestParts) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<TlsInfo>()
+            .cloned()
+            .ok_or(MissingTlsInfo)
+    }
+}
+
+#[derive(Debug)]
+pub struct MissingTlsInfo;
+
+impl Rejection for MissingTlsInfo {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    fn body_text(&self) -> String {
+        "missing TLS info".to_owned()
+    }
+}
+
+impl IntoResponse for MissingTlsInfo {
+    fn into_response(self) -> Response<Body> {
+        (self.status(), self.body_text()).into_response()
+    }
+}
+
+#[derive(Clone)]
+pub struct IntoMakeServiceWithTlsInfo<S, F> {
+    pub(crate) svc: S,
+    pub(crate) tls_info: F,
+}
+
+impl<S, F> MakeService for IntoMakeServiceWithTlsInfo<S, F>
+where
+    S: Service + Clone + Send,
+    F: Fn(&Connection) -> Option<TlsInfo>,
+{
+    type Service = AddTlsInfo<S>;
+    type Error = std::convert::Infallible;
+
+    fn call(&self, conn: &Connection) -> Result<Self::Service, Self::Error> {
+        Ok(AddTlsInfo {
+            svc: self.svc.clone(),
+            tls_info: (self.tls_info)(conn),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct AddTlsInfo<S> {
+    svc: S,
+    tls_info: Option<TlsInfo>,
+}
+
+impl<S: Service> Service for AddTlsInfo<S> {
+    type Body = S::Body;
+    type Error = S::Error;
+
+    fn call(&self, mut req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        if let Some(tls_info) = &self.tls_info {
+            req.extensions_mut().insert(tls_info.clone());
+        }
+        self.svc.call(req)
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.svc.should_continue(req)
+    }
+}
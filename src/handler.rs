@@ -1,6 +1,19 @@
-use std::{convert::Infallible, marker::PhantomData};
+use std::{
+    convert::Infallible,
+    io::{self, Read},
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, AtomicU8, Ordering},
+        Arc,
+    },
+};
 
-use touche::{server::Service, Body, Request, Response};
+use touche::{
+    header,
+    http::{Extensions, HeaderValue},
+    server::Service,
+    Body, HttpBody, Request, Response,
+};
 
 use crate::{
     extract::{FromRequest, FromRequestPart},
@@ -11,10 +24,18 @@ pub trait Handler<T>: Clone + Send + Sized + 'static {
     fn call(self, req: Request<Body>) -> Response<Body>;
 }
 
-#[derive(Clone)]
 pub struct HandlerService<H, T> {
     handler: H,
-    extractors: PhantomData<T>,
+    extractors: PhantomData<fn() -> T>,
+}
+
+impl<H: Clone, T> Clone for HandlerService<H, T> {
+    fn clone(&self) -> Self {
+        Self {
+            handler: self.handler.clone(),
+            extractors: PhantomData,
+        }
+    }
 }
 
 impl<H, T> HandlerService<H, T> {
@@ -34,6 +55,7 @@ where
     type Error = Infallible;
 
     fn call(&self, request: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        BodyClaim::claim(request.extensions());
         Ok(self.handler.clone().call(request))
     }
 }
@@ -43,8 +65,8 @@ where
     F: FnOnce() -> Res + Clone + Send + 'static,
     Res: IntoResponse,
 {
-    fn call(self, _req: Request<Body>) -> Response<Body> {
-        self().into_response()
+    fn call(self, req: Request<Body>) -> Response<Body> {
+        drain(req.into_body(), self().into_response())
     }
 }
 
@@ -64,21 +86,122 @@ macro_rules! impl_handler {
                 $(
                     let $ty = match $ty::from_request_parts(&mut parts) {
                         Ok(val) => val,
-                        Err(rejection) => return rejection.into_response(),
+                        Err(rejection) => return drain(body, rejection.into_response()),
                     };
                 )*
 
+                let chunked = parts.headers.contains_key(header::TRANSFER_ENCODING);
+                let unread = chunked.then(|| {
+                    let unread = UnreadBody::default();
+                    parts.extensions.insert(unread.clone());
+                    unread
+                });
                 let req = Request::from_parts(parts, body);
 
                 let $last = match $last::from_request(req) {
                     Ok(val) => val,
+                    Err(rejection) if chunked => return close(rejection.into_response()),
                     Err(rejection) => return rejection.into_response(),
                 };
 
-                self($($ty,)* $last,).into_response()
+                // The extractors are dropped once the handler returns, draining what is left of
+                // a body they read partially, like `BodyStream`.
+                let res = self($($ty,)* $last,).into_response();
+                match unread {
+                    Some(unread) if unread.is_marked() => close(res),
+                    _ => res,
+                }
             }
         }
     };
 }
 
 all_the_tuples!(impl_handler);
+
+// touche drains the unread bodies with a known length when dropping them, but leaves chunked ones
+// on the connection, where the next request would be parsed from the middle of the body. So the
+// body of rejected requests is drained up to a limit, and past it, or when the body went to an
+// extractor that may not have read it all, the connection is closed after responding.
+const DRAIN_LIMIT: u64 = 64 * 1024;
+
+// Reads what is left of a body of unknown length, returning whether it was read until its end.
+pub(crate) fn drain_body(body: Body) -> bool {
+    if body.len().is_some() {
+        return true;
+    }
+    drain_reader(body.into_reader())
+}
+
+pub(crate) fn drain_reader(reader: impl Read) -> bool {
+    matches!(
+        io::copy(&mut reader.take(DRAIN_LIMIT + 1), &mut io::sink()),
+        Ok(drained) if drained <= DRAIN_LIMIT
+    )
+}
+
+fn drain(body: Body, res: Response<Body>) -> Response<Body> {
+    if drain_body(body) {
+        res
+    } else {
+        close(res)
+    }
+}
+
+// Drains the body of a request answered without reading it, like the router's `404 Not Found`.
+pub(crate) fn drain_request(req: Request<Body>, res: Response<Body>) -> Response<Body> {
+    BodyClaim::claim(req.extensions());
+    drain(req.into_body(), res)
+}
+
+pub(crate) fn close(mut res: Response<Body>) -> Response<Body> {
+    res.headers_mut()
+        .insert(header::CONNECTION, HeaderValue::from_static("close"));
+    res
+}
+
+const UNCLAIMED: u8 = 0;
+const CLAIMED: u8 = 1;
+const CLOSE: u8 = 2;
+
+// Inserted by the outermost router on requests with a body of unknown length, and claimed by the
+// handlers and the router's own responses, which take care of the body. The connection is closed
+// after responding to requests whose body was never claimed, like the ones answered by a layer
+// without calling the inner service, or whose body couldn't be drained.
+#[derive(Clone, Default)]
+pub(crate) struct BodyClaim(Arc<AtomicU8>);
+
+impl BodyClaim {
+    pub(crate) fn claim(extensions: &Extensions) {
+        if let Some(BodyClaim(state)) = extensions.get() {
+            state
+                .compare_exchange(UNCLAIMED, CLAIMED, Ordering::Relaxed, Ordering::Relaxed)
+                .ok();
+        }
+    }
+
+    pub(crate) fn close(extensions: &Extensions) {
+        if let Some(BodyClaim(state)) = extensions.get() {
+            state.store(CLOSE, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn is_claimed(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == CLAIMED
+    }
+}
+
+// Inserted by the handlers on requests with a chunked body before extracting it, and marked by
+// the extractors that couldn't read the body until its end, so the handler closes the connection
+// after responding.
+#[derive(Clone, Default)]
+pub(crate) struct UnreadBody(Arc<AtomicBool>);
+
+impl UnreadBody {
+    pub(crate) fn mark(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_marked(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
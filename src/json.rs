@@ -0,0 +1,180 @@
+use std::io;
+
+use serde::{de::DeserializeOwned, Serialize};
+use touche::{
+    header,
+    http::{HeaderMap, HeaderValue},
+    Body, HttpBody, Request, Response, StatusCode,
+};
+
+use crate::{
+    extract::{rejection_response, FromRequest, Rejection},
+    response::{ErrorResponse, IntoResponse},
+};
+
+/// JSON extractor and response.
+///
+/// As an extractor, it requires an `application/json` (or `application/*+json`) content type and
+/// deserializes the request body into `T`.
+///
+/// As a response, a value that fails to serialize results in a `500 Internal Server Error`, even
+/// inside a tuple like `(StatusCode::CREATED, Json(value))`, whose parts are not applied.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+/// Rejection of the [`Json`] extractor.
+///
+/// On routers with [`error_details`](crate::Router::error_details) enabled, deserialization errors are described on
+/// the response body, including the path of the failing field, its line and column. Other routers
+/// respond with a generic message.
+#[derive(Debug)]
+pub enum JsonRejection {
+    MissingJsonContentType,
+    Io(io::Error),
+    InvalidJson(serde_path_to_error::Error<serde_json::Error>),
+    TrailingData(serde_json::Error),
+}
+
+impl Rejection for JsonRejection {
+    fn status(&self) -> StatusCode {
+        match self {
+            JsonRejection::MissingJsonContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            JsonRejection::Io(_) => StatusCode::BAD_REQUEST,
+            JsonRejection::InvalidJson(_) | JsonRejection::TrailingData(_) => {
+                StatusCode::BAD_REQUEST
+            }
+        }
+    }
+
+    fn body_text(&self) -> String {
+        match self {
+            JsonRejection::MissingJsonContentType => {
+                "expected request with `Content-Type: application/json`".to_owned()
+            }
+            JsonRejection::Io(_) => "error reading body".to_owned(),
+            JsonRejection::InvalidJson(_) | JsonRejection::TrailingData(_) => {
+                "invalid JSON body".to_owned()
+            }
+        }
+    }
+
+    fn detailed_body_text(&self) -> Option<String> {
+        match self {
+            JsonRejection::InvalidJson(err) => Some(format!("invalid JSON body: {err}")),
+            JsonRejection::TrailingData(err) => Some(format!("invalid JSON body: {err}")),
+            _ => None,
+        }
+    }
+}
+
+impl IntoResponse for JsonRejection {
+    fn into_response(self) -> Response<Body> {
+        rejection_response(&self)
+    }
+}
+
+impl<T: DeserializeOwned> FromRequest for Json<T> {
+    type Rejection = JsonRejection;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(req.headers()) {
+            return Err(JsonRejection::MissingJsonContentType);
+        }
+
+        let body = req.into_body().into_bytes().map_err(JsonRejection::Io)?;
+        let de = &mut serde_json::Deserializer::from_slice(&body);
+        let value = serde_path_to_error::deserialize(de).map_err(JsonRejection::InvalidJson)?;
+
+        Ok(Json(value))
+    }
+}
+
+/// JSON extractor like [`Json`], but also rejecting bodies with anything other than whitespace
+/// after the JSON document, which [`Json`] ignores.
+///
+/// Rejecting unknown fields is up to the type, with `#[serde(deny_unknown_fields)]`, which both
+/// extractors honor, as the deserializer can't tell the fields ignored by the type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StrictJson<T>(pub T);
+
+impl<T: DeserializeOwned> FromRequest for StrictJson<T> {
+    type Rejection = JsonRejection;
+
+    fn from_request(req: Request<Body>) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(req.headers()) {
+            return Err(JsonRejection::MissingJsonContentType);
+        }
+
+        let body = req.into_body().into_bytes().map_err(JsonRejection::Io)?;
+        let de = &mut serde_json::Deserializer::from_slice(&body);
+        let value =
+            serde_path_to_error::deserialize(&mut *de).map_err(JsonRejection::InvalidJson)?;
+        de.end().map_err(JsonRejection::TrailingData)?;
+
+        Ok(StrictJson(value))
+    }
+}
+
+pub(crate) fn has_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+    else {
+        return false;
+    };
+
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    match mime.split_once('/') {
+        Some(("application", "json")) => true,
+        Some(("application", subtype)) => subtype.ends_with("+json"),
+        _ => false,
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response<Body> {
+        json_response(serde_json::to_vec(&self.0))
+    }
+}
+
+/// JSON response, indented for readability. Meant for developer-facing endpoints, as [`Json`]
+/// produces smaller bodies.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrettyJson<T>(pub T);
+
+impl<T: Serialize> IntoResponse for PrettyJson<T> {
+    fn into_response(self) -> Response<Body> {
+        json_response(serde_json::to_vec_pretty(&self.0))
+    }
+}
+
+fn json_response(body: serde_json::Result<Vec<u8>>) -> Response<Body> {
+    match body {
+        Ok(body) => {
+            let mut res = Response::builder()
+                .status(StatusCode::OK)
+                .body(Body::from(body))
+                .unwrap();
+            res.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            );
+            res
+        }
+        Err(_) => {
+            let mut res = (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "error serializing JSON response",
+            )
+                .into_response();
+            res.extensions_mut().insert(ErrorResponse);
+            res
+        }
+    }
+}
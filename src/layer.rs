@@ -0,0 +1,58 @@
+//! Middleware wrapping [`Service`](touche::server::Service)s.
+
+mod cors;
+#[cfg(feature = "decompression")]
+mod decompression;
+mod host_filter;
+mod logger;
+#[cfg(feature = "request-id")]
+mod request_id;
+mod timeout;
+
+pub use cors::{Cors, CorsService};
+#[cfg(feature = "decompression")]
+pub use decompression::{RequestDecompression, RequestDecompressionService};
+pub use host_filter::{HostFilter, HostFilterService};
+pub use logger::{LogFormat, Logged, LoggedBody, Logger, LoggerService};
+#[cfg(feature = "request-id")]
+pub use request_id::{SetRequestId, SetRequestIdService};
+pub use timeout::{Timeout, TimeoutService};
+
+/// Wraps an `inner` service into another one, which may run code around the inner service, or
+/// respond without calling it at all.
+///
+/// ```no_run
+/// # use spike::{http::{Request, Response, StatusCode}, layer::Layer, response::IntoResponse, Body};
+/// # use touche::server::Service;
+/// # fn under_maintenance() -> bool { false }
+/// #[derive(Clone)]
+/// struct Maintenance<S>(S);
+///
+/// impl<S: Service<Body = Body>> Service for Maintenance<S> {
+///     type Body = Body;
+///     type Error = S::Error;
+///
+///     fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+///         if under_maintenance() {
+///             Ok(StatusCode::SERVICE_UNAVAILABLE.into_response())
+///         } else {
+///             self.0.call(req)
+///         }
+///     }
+/// }
+///
+/// struct MaintenanceLayer;
+///
+/// impl<S> Layer<S> for MaintenanceLayer {
+///     type Service = Maintenance<S>;
+///
+///     fn layer(&self, inner: S) -> Self::Service {
+///         Maintenance(inner)
+///     }
+/// }
+/// ```
+pub trait Layer<S> {
+    type Service;
+
+    fn layer(&self, inner: S) -> Self::Service;
+}
@@ -0,0 +1,279 @@
+use std::time::Duration;
+
+use touche::{
+    header::{self, HeaderName},
+    http::{HeaderMap, HeaderValue},
+    server::Service,
+    Body, Method, Request, Response, StatusCode,
+};
+
+use crate::{layer::Layer, response::IntoResponse};
+
+#[derive(Debug, Clone)]
+enum Allowed<T> {
+    Any,
+    Only(Vec<T>),
+}
+
+/// Adds the headers of the CORS protocol, allowing browsers to make cross-origin requests.
+///
+/// Preflight requests, the `OPTIONS` requests with an `Access-Control-Request-Method` header, are
+/// answered by the layer with a `204 No Content`, so they don't reach the routes, neither their
+/// `options` handlers nor the [automatic](crate::Router::automatic_options) `OPTIONS` responses.
+/// Other requests are served as usual, and their responses get the allowed origin. Requests from
+/// origins that are not allowed get no CORS headers, so browsers block them.
+///
+/// By default no origin is allowed, and only the `GET`, `HEAD` and `POST` methods, without extra
+/// headers. Allowing any origin sends the `*` wildcard, which browsers reject on requests with
+/// credentials, so credentials require a list of origins. Allowing any method or header sends
+/// back the ones requested by the preflight.
+///
+/// The layer must be added with [`Router::before`](crate::Router::before), so it also runs for
+/// requests answered by the router itself.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use spike::{http::{header, Method}, layer::Cors, routing::get, Router};
+/// # fn list_users() -> &'static str { "users" }
+/// # fn create_user() -> &'static str { "created" }
+/// let router = Router::new()
+///     .route("/users", get(list_users).post(create_user))
+///     .before(
+///         Cors::new()
+///             .allow_origins(["https://example.com"])
+///             .allow_methods([Method::GET, Method::POST])
+///             .allow_headers([header::CONTENT_TYPE])
+///             .max_age(Duration::from_secs(3600)),
+///     );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Cors {
+    origins: Allowed<String>,
+    methods: Allowed<Method>,
+    headers: Allowed<HeaderName>,
+    credentials: bool,
+    max_age: Option<Duration>,
+}
+
+impl Cors {
+    pub fn new() -> Self {
+        Self {
+            origins: Allowed::Only(Vec::new()),
+            methods: Allowed::Only(vec![Method::GET, Method::HEAD, Method::POST]),
+            headers: Allowed::Only(Vec::new()),
+            credentials: false,
+            max_age: None,
+        }
+    }
+
+    /// Allows the given origins, like `https://example.com`, compared ignoring case.
+    pub fn allow_origins<I, O>(self, origins: I) -> Self
+    where
+        I: IntoIterator<Item = O>,
+        O: AsRef<str>,
+    {
+        let origins = origins
+            .into_iter()
+            .map(|origin| origin.as_ref().to_ascii_lowercase())
+            .collect();
+        Self {
+            origins: Allowed::Only(origins),
+            ..self
+        }
+    }
+
+    pub fn allow_any_origin(self) -> Self {
+        Self {
+            origins: Allowed::Any,
+            ..self
+        }
+    }
+
+    pub fn allow_methods(self, methods: impl IntoIterator<Item = Method>) -> Self {
+        Self {
+            methods: Allowed::Only(methods.into_iter().collect()),
+            ..self
+        }
+    }
+
+    pub fn allow_any_method(self) -> Self {
+        Self {
+            methods: Allowed::Any,
+            ..self
+        }
+    }
+
+    /// Allows the given request headers, besides the ones browsers always allow.
+    pub fn allow_headers(self, headers: impl IntoIterator<Item = HeaderName>) -> Self {
+        Self {
+            headers: Allowed::Only(headers.into_iter().collect()),
+            ..self
+        }
+    }
+
+    pub fn allow_any_header(self) -> Self {
+        Self {
+            headers: Allowed::Any,
+            ..self
+        }
+    }
+
+    /// Sets whether requests may include credentials, like cookies. Disabled by default, and only
+    /// possible with a list of allowed origins.
+    pub fn allow_credentials(self, credentials: bool) -> Self {
+        Self {
+            credentials,
+            ..self
+        }
+    }
+
+    /// Sets for how long browsers may cache the preflight responses.
+    pub fn max_age(self, max_age: Duration) -> Self {
+        Self {
+            max_age: Some(max_age),
+            ..self
+        }
+    }
+
+    // Inserts the allowed origin into `headers`, when `origin` is allowed. Unless it is the
+    // wildcard, responses vary by origin even when it is not allowed or missing.
+    fn allow_origin(&self, origin: Option<&HeaderValue>, headers: &mut HeaderMap) -> bool {
+        let wildcard = matches!(self.origins, Allowed::Any);
+        if !wildcard {
+            headers.append(header::VARY, HeaderValue::from_static("origin"));
+        }
+
+        let Some(origin) = origin else {
+            return false;
+        };
+        let allowed = match &self.origins {
+            Allowed::Any => true,
+            Allowed::Only(origins) => origins
+                .iter()
+                .any(|allowed| allowed.as_bytes().eq_ignore_ascii_case(origin.as_bytes())),
+        };
+        if !allowed {
+            return false;
+        }
+
+        let allowed_origin = if wildcard {
+            HeaderValue::from_static("*")
+        } else {
+            origin.clone()
+        };
+        headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, allowed_origin);
+        if self.credentials {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+                HeaderValue::from_static("true"),
+            );
+        }
+        true
+    }
+
+    fn preflight(&self, req: &Request<Body>, origin: &HeaderValue) -> Response<Body> {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            header::VARY,
+            HeaderValue::from_static("access-control-request-method"),
+        );
+        headers.append(
+            header::VARY,
+            HeaderValue::from_static("access-control-request-headers"),
+        );
+
+        if self.allow_origin(Some(origin), &mut headers) {
+            let methods = match &self.methods {
+                Allowed::Any => req
+                    .headers()
+                    .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+                    .cloned(),
+                Allowed::Only(methods) => join(methods.iter().map(Method::as_str)),
+            };
+            let request_headers = match &self.headers {
+                Allowed::Any => req
+                    .headers()
+                    .get(header::ACCESS_CONTROL_REQUEST_HEADERS)
+                    .cloned(),
+                Allowed::Only(names) => join(names.iter().map(HeaderName::as_str)),
+            };
+
+            if let Some(methods) = methods {
+                headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, methods);
+            }
+            if let Some(request_headers) = request_headers {
+                headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, request_headers);
+            }
+            if let Some(max_age) = self.max_age {
+                headers.insert(header::ACCESS_CONTROL_MAX_AGE, max_age.as_secs().into());
+            }
+        }
+
+        (headers, StatusCode::NO_CONTENT).into_response()
+    }
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn join<'a>(values: impl Iterator<Item = &'a str>) -> Option<HeaderValue> {
+    let values = values.collect::<Vec<_>>();
+    if values.is_empty() {
+        return None;
+    }
+    HeaderValue::from_str(&values.join(", ")).ok()
+}
+
+/// # Panics
+///
+/// Panics if any origin is allowed along with credentials, which would let any site make
+/// authenticated requests.
+impl<S> Layer<S> for Cors {
+    type Service = CorsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        if matches!(self.origins, Allowed::Any) && self.credentials {
+            panic!("CORS credentials can't be allowed for any origin, list the allowed origins");
+        }
+        CorsService {
+            inner,
+            cors: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CorsService<S> {
+    inner: S,
+    cors: Cors,
+}
+
+impl<S: Service<Body = Body>> Service for CorsService<S> {
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        let origin = req.headers().get(header::ORIGIN).cloned();
+
+        if let Some(origin) = &origin {
+            if req.method() == Method::OPTIONS
+                && req
+                    .headers()
+                    .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD)
+            {
+                return Ok(self.cors.preflight(&req, origin));
+            }
+        }
+
+        let mut res = self.inner.call(req)?;
+        self.cors.allow_origin(origin.as_ref(), res.headers_mut());
+        Ok(res)
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.inner.should_continue(req)
+    }
+}
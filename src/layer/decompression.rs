@@ -0,0 +1,204 @@
+use std::io::Read;
+
+use flate2::bufread::{DeflateDecoder, MultiGzDecoder, ZlibDecoder};
+
+use touche::{
+    header,
+    http::{HeaderMap, HeaderValue},
+    server::Service,
+    Body, HttpBody, Request, Response, StatusCode,
+};
+
+use crate::{handler::close, layer::Layer, response::IntoResponse};
+
+/// Decodes request bodies compressed with the `gzip` or `deflate` codings of their
+/// `Content-Encoding` header, so the extractors read the decompressed bytes.
+///
+/// Decoded requests have their `Content-Encoding` header removed, and the `Content-Length` of the
+/// decompressed body. Requests with other codings get a `415 Unsupported Media Type`, listing the
+/// supported ones on an `Accept-Encoding` header, bodies that fail to decode a `400 Bad Request`,
+/// and bodies decompressing past the limit, of 8 MiB by default, a `413 Payload Too Large`. The
+/// compressed body is read up to slightly past the limit too, answering larger ones with a
+/// `413 Payload Too Large` that closes the connection.
+///
+/// ```no_run
+/// # use spike::{layer::RequestDecompression, routing::post, Router};
+/// # fn upload(body: String) -> String { body }
+/// let router = Router::new()
+///     .route("/", post(upload))
+///     .before(RequestDecompression::new().limit(64 * 1024 * 1024));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RequestDecompression {
+    limit: usize,
+}
+
+impl RequestDecompression {
+    pub fn new() -> Self {
+        Self {
+            limit: 8 * 1024 * 1024,
+        }
+    }
+
+    /// Sets the maximum size of a decompressed body, in bytes, protecting from decompression bombs.
+    pub fn limit(self, limit: usize) -> Self {
+        Self { limit }
+    }
+}
+
+impl Default for RequestDecompression {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for RequestDecompression {
+    type Service = RequestDecompressionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestDecompressionService {
+            inner,
+            limit: self.limit,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RequestDecompressionService<S> {
+    inner: S,
+    limit: usize,
+}
+
+#[derive(Clone, Copy)]
+enum Coding {
+    Gzip,
+    Deflate,
+}
+
+impl<S: Service<Body = Body>> Service for RequestDecompressionService<S> {
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        let codings = match codings(req.headers()) {
+            Ok(codings) if codings.is_empty() => return self.inner.call(req),
+            Ok(codings) => codings,
+            Err(()) => {
+                let mut res = StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+                res.headers_mut().insert(
+                    header::ACCEPT_ENCODING,
+                    HeaderValue::from_static("gzip, deflate"),
+                );
+                return Ok(res);
+            }
+        };
+
+        // Compressed bodies are rarely larger than the decompressed ones, so reading a bit past the
+        // limit is enough, without buffering an endless body first.
+        let compressed_limit = self
+            .limit
+            .saturating_add(self.limit / 16)
+            .saturating_add(1024);
+        if req
+            .body()
+            .len()
+            .is_some_and(|len| len > compressed_limit as u64)
+        {
+            return Ok(close(StatusCode::PAYLOAD_TOO_LARGE.into_response()));
+        }
+
+        let (mut parts, body) = req.into_parts();
+        let mut compressed = Vec::new();
+        let read = body
+            .into_reader()
+            .take(compressed_limit as u64 + 1)
+            .read_to_end(&mut compressed);
+        let mut body = match read {
+            Ok(len) if len > compressed_limit => {
+                return Ok(close(StatusCode::PAYLOAD_TOO_LARGE.into_response()))
+            }
+            Ok(_) => compressed,
+            Err(_) => {
+                let res = (StatusCode::INTERNAL_SERVER_ERROR, "error reading body");
+                return Ok(close(res.into_response()));
+            }
+        };
+
+        // Codings are listed in the order they were applied, so they are decoded backwards.
+        if !body.is_empty() {
+            for coding in codings.into_iter().rev() {
+                body = match decode(coding, &body, self.limit) {
+                    Ok(decoded) => decoded,
+                    Err(StatusCode::BAD_REQUEST) => {
+                        let res = (StatusCode::BAD_REQUEST, "invalid compressed body");
+                        return Ok(res.into_response());
+                    }
+                    Err(status) => return Ok(status.into_response()),
+                };
+            }
+        }
+
+        parts.headers.remove(header::CONTENT_ENCODING);
+        parts.headers.remove(header::TRANSFER_ENCODING);
+        parts
+            .headers
+            .insert(header::CONTENT_LENGTH, body.len().into());
+
+        self.inner
+            .call(Request::from_parts(parts, Body::from(body)))
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.inner.should_continue(req)
+    }
+}
+
+// Fails on codings other than the supported ones, ignoring `identity`.
+fn codings(headers: &HeaderMap) -> Result<Vec<Coding>, ()> {
+    headers
+        .get_all(header::CONTENT_ENCODING)
+        .iter()
+        .flat_map(|value| value.to_str().unwrap_or("?").split(','))
+        .map(|coding| coding.trim().to_ascii_lowercase())
+        .filter(|coding| !coding.is_empty() && coding != "identity")
+        .map(|coding| match coding.as_str() {
+            "gzip" | "x-gzip" => Ok(Coding::Gzip),
+            "deflate" => Ok(Coding::Deflate),
+            _ => Err(()),
+        })
+        .collect()
+}
+
+// Decodes the whole `body`, failing with a `400 Bad Request` when it is invalid or followed by
+// anything else, and with a `413 Payload Too Large` when it decodes past the `limit`.
+fn decode(coding: Coding, body: &[u8], limit: usize) -> Result<Vec<u8>, StatusCode> {
+    match coding {
+        // A body may hold several concatenated members, which decode to their concatenated
+        // contents.
+        Coding::Gzip => read_whole(MultiGzDecoder::new(body), limit, |_| &[]),
+        // The `deflate` content coding is the zlib format, but some clients send raw DEFLATE
+        // data, which is recognized by the lack of a valid zlib header.
+        Coding::Deflate => match *body {
+            [cmf, flags, ..] if cmf & 0x0f == 8 && u16::from_be_bytes([cmf, flags]) % 31 == 0 => {
+                read_whole(ZlibDecoder::new(body), limit, |decoder| *decoder.get_ref())
+            }
+            _ => read_whole(DeflateDecoder::new(body), limit, |decoder| {
+                *decoder.get_ref()
+            }),
+        },
+    }
+}
+
+fn read_whole<'a, D: Read>(
+    decoder: D,
+    limit: usize,
+    remaining: impl Fn(&D) -> &'a [u8],
+) -> Result<Vec<u8>, StatusCode> {
+    let mut decoded = Vec::new();
+    let mut decoder = decoder.take(limit as u64 + 1);
+    match decoder.read_to_end(&mut decoded) {
+        Ok(len) if len > limit => Err(StatusCode::PAYLOAD_TOO_LARGE),
+        Ok(_) if remaining(decoder.get_ref()).is_empty() => Ok(decoded),
+        _ => Err(StatusCode::BAD_REQUEST),
+    }
+}
@@ -0,0 +1,100 @@
+use touche::{header, server::Service, Body, Request, Response, StatusCode};
+
+use crate::{layer::Layer, response::IntoResponse};
+
+/// Rejects requests for hosts other than the allowed ones, protecting from `Host` header injection.
+///
+/// Hosts are compared without their port and ignoring case, and `*.example.com` allows any
+/// subdomain of `example.com`, but not `example.com` itself. The host is read from the request
+/// target when it is an absolute URI, and from the `Host` header otherwise.
+///
+/// Requests for other hosts get a `421 Misdirected Request`, and requests without a host a
+/// `400 Bad Request`.
+///
+/// ```no_run
+/// # use spike::{layer::HostFilter, routing::get, Router};
+/// # fn index() -> &'static str { "home" }
+/// let router = Router::new()
+///     .route("/", get(index))
+///     .before(HostFilter::new(["example.com", "*.example.com"]));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HostFilter {
+    allowed: Vec<String>,
+}
+
+impl HostFilter {
+    pub fn new<I, H>(allowed: I) -> Self
+    where
+        I: IntoIterator<Item = H>,
+        H: AsRef<str>,
+    {
+        Self {
+            allowed: allowed
+                .into_iter()
+                .map(|host| host.as_ref().to_ascii_lowercase())
+                .collect(),
+        }
+    }
+
+    fn allows(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.allowed
+            .iter()
+            .any(|allowed| match allowed.strip_prefix("*.") {
+                Some(domain) => host
+                    .strip_suffix(domain)
+                    .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+                None => *allowed == host,
+            })
+    }
+}
+
+impl<S> Layer<S> for HostFilter {
+    type Service = HostFilterService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HostFilterService {
+            inner,
+            filter: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HostFilterService<S> {
+    inner: S,
+    filter: HostFilter,
+}
+
+impl<S: Service<Body = Body>> Service for HostFilterService<S> {
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        let host = req.uri().host().map(str::to_owned).or_else(|| {
+            let host = req.headers().get(header::HOST)?.to_str().ok()?;
+            host_without_port(host).map(str::to_owned)
+        });
+
+        match host {
+            Some(host) if self.filter.allows(&host) => self.inner.call(req),
+            Some(_) => Ok(StatusCode::MISDIRECTED_REQUEST.into_response()),
+            None => Ok(StatusCode::BAD_REQUEST.into_response()),
+        }
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.inner.should_continue(req)
+    }
+}
+
+// Strips the port from hosts like `example.com:8080` and `[::1]:8080`.
+fn host_without_port(host: &str) -> Option<&str> {
+    let host = host.trim();
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => &host[..ipv6.find(']')? + 2],
+        None => host.split(':').next()?,
+    };
+    (!host.is_empty()).then_some(host)
+}
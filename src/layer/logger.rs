@@ -0,0 +1,255 @@
+use std::{
+    io::{self, Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use touche::{body::Chunk, header, server::Service, Body, HttpBody, Request, Response, StatusCode};
+
+use crate::{
+    body::{CountingBody, CountingChunks, CountingReader},
+    extract::{ConnectInfo, IntoMakeServiceWithConnectInfo},
+    layer::Layer,
+    HttpDate,
+};
+
+/// Access log formats of the [`Logger`] layer, as defined by the Apache HTTP server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// `host - - [time] "request line" status size`
+    Common,
+    /// The common format followed by the `Referer` and `User-Agent` request headers.
+    #[default]
+    Combined,
+}
+
+/// Writes a line per request on an access log, like:
+///
+/// ```text
+/// 127.0.0.1 - - [06/Nov/1994:08:49:37 +0000] "GET /users?page=2 HTTP/1.1" 200 1337 "-" "curl/8.0.1"
+/// ```
+///
+/// The line is written once the response body is written or dropped, with the size of the body
+/// actually written, so streaming bodies are logged with their size too, and empty ones, like on
+/// `HEAD` requests, with `-`. Services failing to respond are logged right away, with a `500`
+/// status.
+///
+/// The layer wraps the response body, so it goes around the whole application rather than on
+/// [`Router::before`](crate::Router::before). The host is only known when serving with
+/// [`LoggerService::into_make_service_with_connect_info`].
+///
+/// ```no_run
+/// # use spike::{layer::{Layer, Logger}, routing::get, Router};
+/// let router = Router::new().route("/", get(|| "home"));
+/// spike::serve("0.0.0.0:3000", Logger::stdout().layer(router)).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Logger {
+    sink: Arc<Mutex<dyn Write + Send>>,
+    format: LogFormat,
+}
+
+impl Logger {
+    pub fn new(sink: Arc<Mutex<dyn Write + Send>>) -> Self {
+        Self {
+            sink,
+            format: LogFormat::default(),
+        }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(Arc::new(Mutex::new(io::stdout())))
+    }
+
+    pub fn format(self, format: LogFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    // The entry is split around the status and size, which are only known after responding.
+    fn log(&self, entry: &str, headers: &str, status: StatusCode, size: u64) {
+        let size = match size {
+            0 => "-".to_owned(),
+            size => size.to_string(),
+        };
+
+        if let Ok(mut sink) = self.sink.lock() {
+            writeln!(sink, "{entry} {} {size}{headers}", status.as_u16()).ok();
+        }
+    }
+}
+
+impl<S> Layer<S> for Logger {
+    type Service = LoggerService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        LoggerService {
+            inner,
+            logger: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LoggerService<S> {
+    inner: S,
+    logger: Logger,
+}
+
+impl<S> LoggerService<S> {
+    /// Converts the service into a [`MakeService`](touche::server::MakeService) that exposes the
+    /// [`ConnectInfo`] of each request, logging the host of the client.
+    pub fn into_make_service_with_connect_info(self) -> IntoMakeServiceWithConnectInfo<Self> {
+        IntoMakeServiceWithConnectInfo(self)
+    }
+}
+
+impl<S: Service> Service for LoggerService<S> {
+    type Body = LoggedBody<S::Body>;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        let host = req
+            .extensions()
+            .get::<ConnectInfo>()
+            .map_or("-".to_owned(), |ConnectInfo(addr)| addr.ip().to_string());
+
+        // `Sun, 06 Nov 1994 08:49:37 GMT` becomes `06/Nov/1994:08:49:37 +0000`.
+        let date = HttpDate::now().format();
+        let date = match date.split_whitespace().collect::<Vec<_>>()[..] {
+            [_, day, month, year, time, _] => format!("{day}/{month}/{year}:{time} +0000"),
+            _ => date,
+        };
+
+        let target = req
+            .uri()
+            .path_and_query()
+            .map_or(req.uri().path(), |target| target.as_str());
+        let request_line = format!("{} {target} {:?}", req.method(), req.version());
+
+        let entry = format!(
+            "{host} - - [{date}] \"{}\"",
+            escape(request_line.as_bytes())
+        );
+        let mut headers = String::new();
+        if self.logger.format == LogFormat::Combined {
+            for name in [header::REFERER, header::USER_AGENT] {
+                let value = req
+                    .headers()
+                    .get(name)
+                    .map_or(b"-".as_slice(), |value| value.as_bytes());
+                headers.push_str(&format!(" \"{}\"", escape(value)));
+            }
+        }
+
+        match self.inner.call(req) {
+            Ok(res) => {
+                let status = res.status();
+                Ok(res.map(|body| {
+                    let body = CountingBody::new(body);
+                    let log = PendingLog {
+                        logger: self.logger.clone(),
+                        written: body.counter(),
+                        entry,
+                        headers,
+                        status,
+                    };
+                    LoggedBody { body, log }
+                }))
+            }
+            Err(err) => {
+                let status = StatusCode::INTERNAL_SERVER_ERROR;
+                self.logger.log(&entry, &headers, status, 0);
+                Err(err)
+            }
+        }
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.inner.should_continue(req)
+    }
+}
+
+// Escapes quotes, backslashes and non-printable bytes, as the values are logged between quotes.
+fn escape(value: &[u8]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for &byte in value {
+        match byte {
+            b'"' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            }
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\x{byte:02x}")),
+        }
+    }
+    escaped
+}
+
+// Writes the log line when dropped, along with the body or the reader or chunks it was turned
+// into.
+struct PendingLog {
+    logger: Logger,
+    written: Arc<AtomicU64>,
+    entry: String,
+    headers: String,
+    status: StatusCode,
+}
+
+impl Drop for PendingLog {
+    fn drop(&mut self) {
+        let written = self.written.load(Ordering::Relaxed);
+        self.logger
+            .log(&self.entry, &self.headers, self.status, written);
+    }
+}
+
+/// Body of the responses of a [`LoggerService`], logging the request once written or dropped.
+pub struct LoggedBody<B> {
+    body: CountingBody<B>,
+    log: PendingLog,
+}
+
+impl<B: HttpBody> HttpBody for LoggedBody<B> {
+    type Reader = Logged<CountingReader<B::Reader>>;
+    type Chunks = Logged<CountingChunks<B::Chunks>>;
+
+    fn len(&self) -> Option<u64> {
+        self.body.len()
+    }
+
+    fn into_reader(self) -> Self::Reader {
+        Logged {
+            inner: self.body.into_reader(),
+            _log: self.log,
+        }
+    }
+
+    fn into_chunks(self) -> Self::Chunks {
+        Logged {
+            inner: self.body.into_chunks(),
+            _log: self.log,
+        }
+    }
+}
+
+/// Reader or chunks of a [`LoggedBody`], logging the request once dropped.
+pub struct Logged<T> {
+    inner: T,
+    _log: PendingLog,
+}
+
+impl<R: Read> Read for Logged<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<I: Iterator<Item = io::Result<Chunk>>> Iterator for Logged<I> {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use touche::{
+    header::HeaderName, http::HeaderValue, server::Service, Body, Request, Response, StatusCode,
+};
+
+use crate::{extract::RequestId, layer::Layer};
+
+type GeneratorFn = Arc<dyn Fn() -> String + Send + Sync>;
+
+/// Gives every request a [`RequestId`], taken from its `X-Request-Id` header, or generated when
+/// the header is missing, as a random UUID by default. The id is set on the request header, so
+/// services proxied to get it too, and on the response, unless it sets its own.
+///
+/// As ids end up in logs, incoming ones longer than [`MAX_LEN`](SetRequestId::MAX_LEN) bytes, or
+/// with characters other than ASCII letters, digits and `-_.:+/=`, are replaced by generated ones.
+///
+/// ```no_run
+/// # use spike::{extract::RequestId, layer::SetRequestId, routing::get, Router};
+/// let router = Router::new()
+///     .route("/", get(|id: RequestId| format!("request {id}")))
+///     .before(SetRequestId::new());
+/// ```
+#[derive(Clone)]
+pub struct SetRequestId {
+    header: HeaderName,
+    generator: GeneratorFn,
+}
+
+impl SetRequestId {
+    /// Max length of the incoming ids kept.
+    pub const MAX_LEN: usize = 128;
+
+    pub fn new() -> Self {
+        Self {
+            header: HeaderName::from_static("x-request-id"),
+            generator: Arc::new(|| uuid::Uuid::new_v4().to_string()),
+        }
+    }
+
+    pub fn header(self, header: HeaderName) -> Self {
+        Self { header, ..self }
+    }
+
+    /// Sets the function generating the ids of requests without one. Ids that are not valid
+    /// header values are only kept on the [`RequestId`].
+    pub fn generator<F>(self, generator: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        Self {
+            generator: Arc::new(generator),
+            ..self
+        }
+    }
+}
+
+impl Default for SetRequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for SetRequestId {
+    type Service = SetRequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SetRequestIdService {
+            inner,
+            config: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SetRequestIdService<S> {
+    inner: S,
+    config: SetRequestId,
+}
+
+impl<S: Service<Body = Body>> Service for SetRequestIdService<S> {
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, mut req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        let header = &self.config.header;
+        let id = match req.headers().get(header).and_then(|id| id.to_str().ok()) {
+            Some(id) if is_valid(id) => id.to_owned(),
+            _ => {
+                let id = (self.config.generator)();
+                match HeaderValue::from_str(&id) {
+                    Ok(value) => req.headers_mut().insert(header.clone(), value),
+                    Err(_) => req.headers_mut().remove(header),
+                };
+                id
+            }
+        };
+        req.extensions_mut().insert(RequestId(id.into()));
+
+        let value = req.headers().get(header).cloned();
+        let mut res = self.inner.call(req)?;
+        if let Some(value) = value {
+            res.headers_mut().entry(header).or_insert(value);
+        }
+        Ok(res)
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.inner.should_continue(req)
+    }
+}
+
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= SetRequestId::MAX_LEN
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"-_.:+/=".contains(&b))
+}
@@ -0,0 +1,144 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use touche::{header, http::HeaderValue, server::Service, Body, Request, Response, StatusCode};
+
+use crate::{layer::Layer, response::IntoResponse};
+
+/// Responds with a `503 Service Unavailable` to requests whose service takes longer than the
+/// timeout to respond.
+///
+/// The service runs on its own thread for each request, waited for up to the timeout. Threads can't
+/// be stopped, so a service that times out keeps running on its thread until it returns, and its
+/// response is discarded. As it may still be reading the request body, the timeout response closes
+/// the connection with `Connection: close`. A service that panics gets a `500 Internal Server
+/// Error`, also closing the connection.
+///
+/// Services that never return leak their threads, so [`Timeout::max_threads`] bounds the threads
+/// running at once, counting the ones left running after a timeout. Past the limit, requests get a
+/// `503 Service Unavailable` without calling the service, as they do when the thread can't be
+/// spawned.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use spike::{layer::Timeout, routing::get, Router};
+/// # fn report() -> &'static str { "report" }
+/// let router = Router::new()
+///     .route("/report", get(report))
+///     .before(Timeout::new(Duration::from_secs(30)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timeout {
+    timeout: Duration,
+    max_threads: usize,
+    running: Arc<AtomicUsize>,
+}
+
+impl Timeout {
+    /// Times out requests whose service doesn't respond within `timeout`, with no limit on the
+    /// threads running the services.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            max_threads: usize::MAX,
+            running: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Sets how many threads the services may run on at once, unbounded by default.
+    pub fn max_threads(mut self, max_threads: usize) -> Self {
+        self.max_threads = max_threads;
+        self
+    }
+}
+
+impl<S> Layer<S> for Timeout {
+    type Service = TimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutService {
+            inner,
+            timeout: self.clone(),
+        }
+    }
+}
+
+/// Service running `S` on its own thread for each request, created by the [`Timeout`] layer.
+#[derive(Clone)]
+pub struct TimeoutService<S> {
+    inner: S,
+    timeout: Timeout,
+}
+
+impl<S> Service for TimeoutService<S>
+where
+    S: Service<Body = Body> + Clone + Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        let Some(running) = Running::acquire(&self.timeout) else {
+            return Ok(closing(StatusCode::SERVICE_UNAVAILABLE));
+        };
+
+        let (tx, rx) = mpsc::sync_channel(1);
+        let inner = self.inner.clone();
+        let spawned = thread::Builder::new().spawn(move || {
+            let _running = running;
+            tx.send(inner.call(req)).ok()
+        });
+        if spawned.is_err() {
+            return Ok(closing(StatusCode::SERVICE_UNAVAILABLE));
+        }
+
+        let status = match rx.recv_timeout(self.timeout.timeout) {
+            Ok(res) => return res,
+            Err(RecvTimeoutError::Timeout) => StatusCode::SERVICE_UNAVAILABLE,
+            Err(RecvTimeoutError::Disconnected) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+
+        Ok(closing(status))
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.inner.should_continue(req)
+    }
+}
+
+// The request may not have been read, so the connection is closed.
+fn closing(status: StatusCode) -> Response<Body> {
+    let mut res = status.into_response();
+    res.headers_mut()
+        .insert(header::CONNECTION, HeaderValue::from_static("close"));
+    res
+}
+
+// A slot on the running threads, released when dropped by the thread.
+struct Running(Arc<AtomicUsize>);
+
+impl Running {
+    fn acquire(timeout: &Timeout) -> Option<Self> {
+        timeout
+            .running
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                (running < timeout.max_threads).then_some(running + 1)
+            })
+            .ok()?;
+        Some(Running(timeout.running.clone()))
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
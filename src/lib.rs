@@ -1,13 +1,204 @@
 #[macro_use]
 mod macros;
 
+pub mod body;
+mod date;
 pub mod extract;
 mod handler;
+#[cfg(feature = "json")]
+mod json;
+pub mod layer;
+pub mod metrics;
+#[cfg(feature = "json")]
+mod problem;
 pub mod response;
 pub mod routing;
+pub mod services;
+mod shutdown;
+pub mod test;
 
+use std::{
+    io, iter,
+    net::{TcpListener, ToSocketAddrs},
+    time::Duration,
+};
+
+use touche::server::{ServerBuilder, Service};
+
+pub use crate::date::{DateOutOfRange, HttpDate};
+#[cfg(feature = "json")]
+pub use crate::json::Json;
 pub use crate::routing::Router;
+#[cfg(feature = "macros")]
+pub use spike_macros::debug_handler;
 
 #[doc(no_inline)]
 pub use touche::http;
-pub use touche::Server;
+pub use touche::{Body, Server};
+
+/// Binds to `addr` and serves the `service`.
+///
+/// This is a thin wrapper over [`Server`], which is still available for finer control. See
+/// [`ServerConfig`] for tuning options.
+pub fn serve<A, S>(addr: A, service: S) -> io::Result<()>
+where
+    A: ToSocketAddrs,
+    S: Service + Send + Clone + 'static,
+{
+    ServerConfig::default().serve(addr, service)
+}
+
+/// Binds to `addr` and serves the `service` until `shutdown` returns, then shuts down gracefully.
+///
+/// See [`ServerConfig::serve_with_graceful_shutdown`].
+pub fn serve_with_graceful_shutdown<A, S, F>(addr: A, service: S, shutdown: F) -> io::Result<()>
+where
+    A: ToSocketAddrs,
+    S: Service + Send + Clone + 'static,
+    F: FnOnce() + Send + 'static,
+{
+    ServerConfig::default().serve_with_graceful_shutdown(addr, service, shutdown)
+}
+
+/// Tuning options for serving an application, mapped onto touche's server builder.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    max_threads: usize,
+    read_timeout: Option<Duration>,
+    shutdown_grace_period: Duration,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_threads: 512,
+            read_timeout: None,
+            shutdown_grace_period: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ServerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Max number of threads serving connections. Defaults to `512`.
+    ///
+    /// Each connection is served by its own thread, so this also limits the connections being
+    /// served at the same time, while new ones wait to be accepted.
+    pub fn max_threads(self, max_threads: usize) -> Self {
+        Self {
+            max_threads,
+            ..self
+        }
+    }
+
+    /// Closes connections that don't send any data within the `timeout`. Defaults to no timeout.
+    ///
+    /// This doubles as the keep-alive timeout, as idle connections are waiting for data.
+    pub fn read_timeout<T: Into<Option<Duration>>>(self, timeout: T) -> Self {
+        Self {
+            read_timeout: timeout.into(),
+            ..self
+        }
+    }
+
+    /// How long a graceful shutdown waits for the open connections to finish before closing them.
+    /// Defaults to 30 seconds.
+    pub fn shutdown_grace_period(self, shutdown_grace_period: Duration) -> Self {
+        Self {
+            shutdown_grace_period,
+            ..self
+        }
+    }
+
+    /// Binds the server to `addr`, with a friendlier error when the address is already in use.
+    pub fn bind<A: ToSocketAddrs>(self, addr: A) -> io::Result<Server<'static>> {
+        let listener = listen(addr)?;
+        let connections = iter::from_fn(move || Some(listener.accept().ok()?.into()));
+        Ok(self.builder().from_connections(connections))
+    }
+
+    /// Binds to `addr` and serves the `service`.
+    pub fn serve<A, S>(self, addr: A, service: S) -> io::Result<()>
+    where
+        A: ToSocketAddrs,
+        S: Service + Send + Clone + 'static,
+    {
+        self.serve_listener(listen(addr)?, service)
+    }
+
+    /// Serves the `service` on a bound `listener`, like one bound to port `0` whose
+    /// [local address](TcpListener::local_addr) is needed to connect to it.
+    pub fn serve_listener<S>(self, listener: TcpListener, service: S) -> io::Result<()>
+    where
+        S: Service + Send + Clone + 'static,
+    {
+        let connections = iter::from_fn(move || Some(listener.accept().ok()?.into()));
+        self.builder().from_connections(connections).serve(service)
+    }
+
+    /// Binds to `addr` and serves the `service` until `shutdown` returns. `shutdown` runs on its
+    /// own thread, and should block until the server must stop, like when a signal is received.
+    ///
+    /// Then the server stops accepting connections, and closes the keep-alive ones after their
+    /// current request, or right away when they are idle. It returns once every connection is
+    /// closed, or after the [grace period](ServerConfig::shutdown_grace_period), closing the ones
+    /// still open.
+    pub fn serve_with_graceful_shutdown<A, S, F>(
+        self,
+        addr: A,
+        service: S,
+        shutdown: F,
+    ) -> io::Result<()>
+    where
+        A: ToSocketAddrs,
+        S: Service + Send + Clone + 'static,
+        F: FnOnce() + Send + 'static,
+    {
+        self.serve_listener_with_graceful_shutdown(listen(addr)?, service, shutdown)
+    }
+
+    /// Serves the `service` on a bound `listener` until `shutdown` returns. See
+    /// [`serve_with_graceful_shutdown`](ServerConfig::serve_with_graceful_shutdown).
+    pub fn serve_listener_with_graceful_shutdown<S, F>(
+        self,
+        listener: TcpListener,
+        service: S,
+        shutdown: F,
+    ) -> io::Result<()>
+    where
+        S: Service + Send + Clone + 'static,
+        F: FnOnce() + Send + 'static,
+    {
+        let grace = self.shutdown_grace_period;
+        shutdown::serve(listener, self.builder(), service, shutdown, grace)
+    }
+
+    fn builder(&self) -> ServerBuilder {
+        Server::builder()
+            .max_threads(self.max_threads)
+            .read_timeout(self.read_timeout)
+    }
+}
+
+// Binds to `addr`, with a friendlier error when the address is already in use.
+fn listen<A: ToSocketAddrs>(addr: A) -> io::Result<TcpListener> {
+    let addrs = addr.to_socket_addrs()?.collect::<Vec<_>>();
+
+    TcpListener::bind(addrs.as_slice()).map_err(|err| match err.kind() {
+        io::ErrorKind::AddrInUse => io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!(
+                "address {} is already in use, is another server running?",
+                addrs
+                    .iter()
+                    .map(|addr| addr.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ),
+        _ => err,
+    })
+}
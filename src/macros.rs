@@ -42,3 +42,41 @@ macro_rules! all_the_tuples_no_last_special_case {
         $name!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
     };
 }
+
+/// Declares an extractor reading a request header as a `String`, for one-off custom headers.
+/// Requests without the header, or with a value that isn't visible ASCII, are rejected with a
+/// `400 Bad Request`.
+///
+/// ```no_run
+/// # use spike::header_extractor;
+/// header_extractor!(pub TenantId, "x-tenant-id");
+///
+/// fn handler(TenantId(tenant): TenantId) -> String {
+///     format!("Hello {tenant}")
+/// }
+/// ```
+#[macro_export]
+macro_rules! header_extractor {
+    ($(#[$meta:meta])* $vis:vis $name:ident, $header:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        $vis struct $name(pub String);
+
+        impl $crate::extract::FromRequestPart for $name {
+            type Rejection = $crate::extract::HeaderRejection;
+
+            fn from_request_parts(
+                parts: &mut $crate::http::request::Parts,
+            ) -> Result<Self, Self::Rejection> {
+                let value = parts
+                    .headers
+                    .get($header)
+                    .ok_or($crate::extract::HeaderRejection::Missing($header))?;
+                let value = value
+                    .to_str()
+                    .map_err(|_| $crate::extract::HeaderRejection::Invalid($header))?;
+                Ok($name(value.to_owned()))
+            }
+        }
+    };
+}
@@ -0,0 +1,75 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use touche::StatusCode;
+
+/// Request and response counters of a [`Router`](crate::Router).
+///
+/// Cloning the handle is cheap, and every clone reads the same counters. Body sizes are only
+/// accounted for bodies with a known length.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics(Arc<Counters>);
+
+#[derive(Debug, Default)]
+struct Counters {
+    requests: AtomicU64,
+    responses: [AtomicU64; 5],
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl Metrics {
+    pub fn requests(&self) -> u64 {
+        self.0.requests.load(Ordering::Relaxed)
+    }
+
+    pub fn informational_responses(&self) -> u64 {
+        self.responses(1)
+    }
+
+    pub fn successful_responses(&self) -> u64 {
+        self.responses(2)
+    }
+
+    pub fn redirection_responses(&self) -> u64 {
+        self.responses(3)
+    }
+
+    pub fn client_error_responses(&self) -> u64 {
+        self.responses(4)
+    }
+
+    pub fn server_error_responses(&self) -> u64 {
+        self.responses(5)
+    }
+
+    pub fn bytes_in(&self) -> u64 {
+        self.0.bytes_in.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_out(&self) -> u64 {
+        self.0.bytes_out.load(Ordering::Relaxed)
+    }
+
+    fn responses(&self, class: usize) -> u64 {
+        self.0.responses[class - 1].load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_request(&self, body_len: Option<u64>) {
+        self.0.requests.fetch_add(1, Ordering::Relaxed);
+        if let Some(len) = body_len {
+            self.0.bytes_in.fetch_add(len, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_response(&self, status: StatusCode, body_len: Option<u64>) {
+        if let Some(counter) = self.0.responses.get((status.as_u16() / 100) as usize - 1) {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Some(len) = body_len {
+            self.0.bytes_out.fetch_add(len, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use serde_json::{json, Value};
+use touche::{header, http::HeaderValue, Body, Response, StatusCode};
+
+use crate::{extract::Rejection, response::IntoResponse};
+
+/// An error rendered as a problem details document, as described by
+/// [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807), with the `application/problem+json`
+/// content type.
+///
+/// The `type` defaults to `about:blank`, and the `title` to the reason phrase of the status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    status: StatusCode,
+    type_uri: String,
+    title: String,
+    detail: Option<String>,
+}
+
+impl Problem {
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            type_uri: "about:blank".to_owned(),
+            title: status.canonical_reason().unwrap_or_default().to_owned(),
+            detail: None,
+        }
+    }
+
+    /// Sets the URI identifying the problem type.
+    pub fn with_type(self, type_uri: impl Into<String>) -> Self {
+        Self {
+            type_uri: type_uri.into(),
+            ..self
+        }
+    }
+
+    pub fn with_title(self, title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            ..self
+        }
+    }
+
+    /// Sets the explanation specific to this occurrence of the problem.
+    pub fn with_detail(self, detail: impl Into<String>) -> Self {
+        Self {
+            detail: Some(detail.into()),
+            ..self
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+/// Uses the status of the rejection, and its body text as the detail.
+impl<R: Rejection> From<R> for Problem {
+    fn from(rejection: R) -> Self {
+        Problem::new(rejection.status()).with_detail(rejection.body_text())
+    }
+}
+
+impl IntoResponse for Problem {
+    fn into_response(self) -> Response<Body> {
+        let mut problem = json!({
+            "type": self.type_uri,
+            "title": self.title,
+            "status": self.status.as_u16(),
+        });
+        if let Some(detail) = self.detail {
+            problem["detail"] = Value::String(detail);
+        }
+
+        let mut res = Response::builder()
+            .status(self.status)
+            .body(Body::from(problem.to_string()))
+            .unwrap();
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/problem+json"),
+        );
+        res
+    }
+}
@@ -1,11 +1,33 @@
-use std::{borrow::Cow, convert::Infallible};
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    error::Error,
+    fmt,
+    fs::File,
+    io::{self, Cursor, Read},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use touche::{
     header,
-    http::{response::Parts as ResponseParts, HeaderValue},
-    Body, Response, StatusCode,
+    http::{
+        self,
+        header::{InvalidHeaderName, InvalidHeaderValue},
+        response::Parts as ResponseParts,
+        HeaderName, HeaderValue,
+    },
+    Body, HeaderMap, HttpBody, Response, StatusCode,
 };
 
+use crate::{extract::Extension, DateOutOfRange, HttpDate};
+
+#[cfg(feature = "json")]
+pub use crate::json::PrettyJson;
+#[cfg(feature = "json")]
+pub use crate::problem::Problem;
+
 pub trait IntoResponse {
     fn into_response(self) -> Response<Body>;
 }
@@ -18,6 +40,20 @@ pub trait IntoResponseParts {
 
 macro_rules! impl_into_response {
     ($($ty:ident),* $(,)?) => {
+        /// The last element of the tuple always builds the response, and the preceding elements are
+        /// then applied to it as parts, from left to right.
+        ///
+        /// As `StatusCode` implements both traits, `(headers, StatusCode::NO_CONTENT)` becomes an
+        /// empty response with the given status and headers, while on `(StatusCode::CREATED,
+        /// StatusCode::OK)` the leading part wins, resulting in a `201 Created`.
+        ///
+        /// As the parts are applied after the response is built, headers like `Content-Type` set on
+        /// the parts win over the ones set by the last element, so `([(header::CONTENT_TYPE,
+        /// "text/html")], "<b>hi</b>")` is sent as `text/html` instead of `text/plain`.
+        ///
+        /// When the last element is a `Result`, the parts are only applied to the `Ok` response.
+        /// An `Err` replaces the whole response, so `(StatusCode::CREATED, Err(StatusCode::CONFLICT))`
+        /// results in a `409 Conflict`.
         #[allow(non_snake_case, unused_parens)]
         impl<R, $($ty,)*> IntoResponse for ($($ty),*, R)
         where
@@ -28,6 +64,9 @@ macro_rules! impl_into_response {
                 let ($($ty),*, res) = self;
 
                 let res = res.into_response();
+                if res.extensions().get::<ErrorResponse>().is_some() {
+                    return res;
+                }
                 let (parts, body) = res.into_parts();
 
                 $(
@@ -47,12 +86,81 @@ macro_rules! impl_into_response {
 
 all_the_tuples_no_last_special_case!(impl_into_response);
 
+/// Lets handlers return `Result<T, Infallible>`, which always responds with the `Ok` value.
 impl IntoResponse for Infallible {
     fn into_response(self) -> Response<Body> {
         match self {}
     }
 }
 
+// Marks responses built from errors, so response tuples don't apply their parts on them.
+#[derive(Clone, Copy)]
+pub(crate) struct ErrorResponse;
+
+// The body text of an error response along with details about its cause, replacing the body on
+// routers with `error_details` enabled.
+#[derive(Clone)]
+pub(crate) struct ErrorDetails(pub(crate) String);
+
+// Replaces the body of error responses with their details when `show` is set, dropping the details
+// otherwise, so outer routers don't show them either.
+pub(crate) fn error_details(mut res: Response<Body>, show: bool) -> Response<Body> {
+    match res.extensions_mut().remove::<ErrorDetails>() {
+        Some(ErrorDetails(details)) if show => {
+            let (mut parts, _) = res.into_parts();
+            parts
+                .headers
+                .insert(header::CONTENT_LENGTH, details.len().into());
+            parts
+                .headers
+                .entry(header::CONTENT_TYPE)
+                .or_insert(HeaderValue::from_static("text/plain;charset=utf-8"));
+            Response::from_parts(parts, Body::from(details))
+        }
+        _ => res,
+    }
+}
+
+impl<T, E> IntoResponse for Result<T, E>
+where
+    T: IntoResponse,
+    E: IntoResponse,
+{
+    fn into_response(self) -> Response<Body> {
+        match self {
+            Ok(res) => res.into_response(),
+            Err(err) => {
+                let mut res = err.into_response();
+                res.extensions_mut().insert(ErrorResponse);
+                res
+            }
+        }
+    }
+}
+
+/// Renders as a `500 Internal Server Error`, so handlers can use `?` on any error by returning
+/// `Result<T, Box<dyn Error + Send + Sync>>`. The error is described on the response body on routers
+/// with [`error_details`](crate::Router::error_details) enabled only.
+impl IntoResponse for Box<dyn Error + Send + Sync> {
+    fn into_response(self) -> Response<Body> {
+        let mut res = StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        res.extensions_mut().insert(ErrorDetails(self.to_string()));
+        res
+    }
+}
+
+/// Renders as a `500 Internal Server Error`, describing the error and its causes on the response
+/// body on routers with [`error_details`](crate::Router::error_details) enabled only.
+#[cfg(feature = "anyhow")]
+impl IntoResponse for anyhow::Error {
+    fn into_response(self) -> Response<Body> {
+        let mut res = StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        res.extensions_mut()
+            .insert(ErrorDetails(format!("{self:#}")));
+        res
+    }
+}
+
 impl IntoResponseParts for StatusCode {
     type Error = Infallible;
 
@@ -62,12 +170,105 @@ impl IntoResponseParts for StatusCode {
     }
 }
 
+/// Replaces the headers of the response with the same names, like the `Content-Type` set by the
+/// body.
+impl IntoResponseParts for HeaderMap {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.headers.extend(self);
+        Ok(res)
+    }
+}
+
+/// Headers as name and value pairs, e.g. `([(header::CONTENT_TYPE, "text/html")], body)`,
+/// replacing the headers of the response with the same names. A name repeated on the pairs keeps
+/// all of its values, like several `Set-Cookie`. An invalid name or value results in a
+/// `500 Internal Server Error`.
+impl<K, V, const N: usize> IntoResponseParts for [(K, V); N]
+where
+    K: TryInto<HeaderName>,
+    K::Error: Into<http::Error>,
+    V: TryInto<HeaderValue>,
+    V::Error: Into<http::Error>,
+{
+    type Error = InvalidHeader;
+
+    fn into_response_parts(self, res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        insert_headers(res, self)
+    }
+}
+
+/// Like the array of pairs, for sets of headers only known at runtime.
+impl<K, V> IntoResponseParts for Vec<(K, V)>
+where
+    K: TryInto<HeaderName>,
+    K::Error: Into<http::Error>,
+    V: TryInto<HeaderValue>,
+    V::Error: Into<http::Error>,
+{
+    type Error = InvalidHeader;
+
+    fn into_response_parts(self, res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        insert_headers(res, self)
+    }
+}
+
+fn insert_headers<K, V>(
+    mut res: ResponseParts,
+    headers: impl IntoIterator<Item = (K, V)>,
+) -> Result<ResponseParts, InvalidHeader>
+where
+    K: TryInto<HeaderName>,
+    K::Error: Into<http::Error>,
+    V: TryInto<HeaderValue>,
+    V::Error: Into<http::Error>,
+{
+    // The first value of a name replaces the ones already on the response, and the next ones are
+    // appended, so the pairs can repeat a name like `Set-Cookie`.
+    let mut inserted = Vec::new();
+    for (name, value) in headers {
+        let name: HeaderName = name.try_into().map_err(Into::into)?;
+        let value = value.try_into().map_err(Into::into)?;
+        if inserted.contains(&name) {
+            res.headers.append(name, value);
+        } else {
+            res.headers.insert(name.clone(), value);
+            inserted.push(name);
+        }
+    }
+    Ok(res)
+}
+
+/// An empty response with `Content-Length: 0`, except for the statuses that can't have a body, the
+/// `1xx`, `204 No Content` and `304 Not Modified`.
 impl IntoResponse for StatusCode {
     fn into_response(self) -> Response<Body> {
-        Response::builder()
-            .status(self)
-            .body(Body::empty())
-            .unwrap()
+        let mut res = Response::builder().status(self);
+        if !(self.is_informational()
+            || self == StatusCode::NO_CONTENT
+            || self == StatusCode::NOT_MODIFIED)
+        {
+            res = res.header(header::CONTENT_LENGTH, 0);
+        }
+        res.body(Body::empty()).unwrap()
+    }
+}
+
+/// A status code response that, on client and server errors, carries the canonical reason phrase
+/// (e.g. `Not Found`) as a `text/plain` body. Other statuses get an empty body, like a bare
+/// `StatusCode`.
+#[derive(Debug, Clone, Copy)]
+pub struct StatusCodeWithBody(pub StatusCode);
+
+impl IntoResponse for StatusCodeWithBody {
+    fn into_response(self) -> Response<Body> {
+        match self.0.canonical_reason() {
+            Some(reason) if self.0.is_client_error() || self.0.is_server_error() => {
+                (self.0, reason).into_response()
+            }
+            _ => self.0.into_response(),
+        }
     }
 }
 
@@ -105,6 +306,43 @@ impl IntoResponse for String {
     }
 }
 
+impl IntoResponse for Cow<'static, str> {
+    fn into_response(self) -> Response<Body> {
+        match self {
+            Cow::Borrowed(body) => body.into_response(),
+            Cow::Owned(body) => body.into_response(),
+        }
+    }
+}
+
+/// Streams the shared string without copying it.
+impl IntoResponse for Arc<str> {
+    fn into_response(self) -> Response<Body> {
+        let mut res = Arc::<[u8]>::from(self).into_response();
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain;charset=utf-8"),
+        );
+        res
+    }
+}
+
+/// Streams the shared bytes without copying them.
+impl IntoResponse for Arc<[u8]> {
+    fn into_response(self) -> Response<Body> {
+        let len = self.len();
+        let mut res = Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::from_reader(Cursor::new(self), len))
+            .unwrap();
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/octet-stream"),
+        );
+        res
+    }
+}
+
 impl IntoResponse for &'static [u8] {
     fn into_response(self) -> Response<Body> {
         Cow::Borrowed(self).into_response()
@@ -142,3 +380,440 @@ impl IntoResponse for Cow<'static, [u8]> {
         res
     }
 }
+
+/// Streams the file at the path, with a content type guessed from its extension, and its
+/// modification time as `Last-Modified`, when the filesystem provides it.
+///
+/// Responds with `404 Not Found` when the file doesn't exist or is a directory, `403 Forbidden`
+/// when it can't be read, and `500 Internal Server Error` on other IO errors. The path is used as
+/// is, so handlers building it from request data must guard against path traversal themselves.
+impl IntoResponse for PathBuf {
+    fn into_response(self) -> Response<Body> {
+        let body = File::open(&self).and_then(|file| {
+            let metadata = file.metadata()?;
+            if metadata.is_dir() {
+                return Err(io::ErrorKind::NotFound.into());
+            }
+            Ok((Body::try_from(file)?, metadata.modified().ok()))
+        });
+
+        match body {
+            Ok((body, modified)) => {
+                let mut res = Response::builder()
+                    .status(StatusCode::OK)
+                    .body(body)
+                    .unwrap();
+                res.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(guess_content_type(&self)),
+                );
+                if let Some(modified) = modified.and_then(|time| HttpDate::try_from(time).ok()) {
+                    res.headers_mut()
+                        .insert(header::LAST_MODIFIED, modified.into());
+                }
+                res
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                StatusCode::NOT_FOUND.into_response()
+            }
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                StatusCode::FORBIDDEN.into_response()
+            }
+            Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+fn guess_content_type(path: &std::path::Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html;charset=utf-8",
+        "css" => "text/css;charset=utf-8",
+        "js" | "mjs" => "text/javascript;charset=utf-8",
+        "json" => "application/json",
+        "txt" => "text/plain;charset=utf-8",
+        "csv" => "text/csv;charset=utf-8",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        "wasm" => "application/wasm",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "mp4" => "video/mp4",
+        "mp3" => "audio/mpeg",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A binary response whose content type is sniffed from the leading bytes of the body, for common
+/// image, document and archive formats. Unknown formats are sent as `application/octet-stream`,
+/// like a bare `Vec<u8>`.
+#[derive(Debug, Clone)]
+pub struct Sniffed(pub Vec<u8>);
+
+impl IntoResponse for Sniffed {
+    fn into_response(self) -> Response<Body> {
+        let content_type = sniff_content_type(&self.0);
+        let mut res = self.0.into_response();
+        res.headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        res
+    }
+}
+
+fn sniff_content_type(body: &[u8]) -> &'static str {
+    match body {
+        [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => "image/png",
+        [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+        [b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [0x00, 0x00, 0x01, 0x00, ..] => "image/x-icon",
+        [b'%', b'P', b'D', b'F', b'-', ..] => "application/pdf",
+        [b'P', b'K', 0x03, 0x04, ..] => "application/zip",
+        [0x1f, 0x8b, ..] => "application/gzip",
+        [0x00, b'a', b's', b'm', ..] => "application/wasm",
+        [b'O', b'g', b'g', b'S', ..] => "audio/ogg",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Sends the response with chunked transfer encoding, even when the length of its body is known,
+/// which would otherwise be sent as `Content-Length`.
+///
+/// HTTP/1.0 clients, which don't support chunked encoding, still get a `Content-Length` when the
+/// length is known. Responses to `HEAD` requests keep the `Transfer-Encoding` header, without a
+/// body.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ForceChunked<T>(pub T);
+
+impl<T: IntoResponse> IntoResponse for ForceChunked<T> {
+    fn into_response(self) -> Response<Body> {
+        let mut res = self.0.into_response();
+        res.headers_mut().remove(header::CONTENT_LENGTH);
+        res.headers_mut().insert(
+            header::TRANSFER_ENCODING,
+            HeaderValue::from_static("chunked"),
+        );
+        res
+    }
+}
+
+/// Sends the response with the given `Content-Length`, even when its body is a stream of unknown
+/// length, which would otherwise be sent with chunked encoding.
+///
+/// Streams must produce exactly that many bytes, as the client would wait for the missing ones,
+/// or read the extra ones as the next response. Bodies whose length is known and differs respond
+/// with a `500 Internal Server Error`. Responses to `HEAD` requests keep the `Content-Length` header,
+/// without a body. Layers that re-encode the body, like compression, must replace it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WithContentLength<T>(pub T, pub u64);
+
+impl<T: IntoResponse> IntoResponse for WithContentLength<T> {
+    fn into_response(self) -> Response<Body> {
+        let WithContentLength(res, len) = self;
+        let (mut parts, body) = res.into_response().into_parts();
+
+        if body.len().is_some_and(|body_len| body_len != len) {
+            let mut res = (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "content length doesn't match the body",
+            )
+                .into_response();
+            res.extensions_mut().insert(ErrorResponse);
+            return res;
+        }
+
+        parts.headers.remove(header::TRANSFER_ENCODING);
+        parts.headers.insert(header::CONTENT_LENGTH, len.into());
+        Response::from_parts(parts, body)
+    }
+}
+
+/// Builds a `text/plain` response by copying the given string.
+///
+/// There is no `IntoResponse` for borrowed strings, as the response outlives the handler that
+/// built it, and a blanket impl over `AsRef<str>` would conflict with the impls above. This helper
+/// copies the string before the handler returns, so `text(&buf[..len])` works.
+pub fn text(body: impl AsRef<str>) -> impl IntoResponse {
+    body.as_ref().to_owned()
+}
+
+/// Error of response parts built from an invalid header name or value, rendered as a `500`.
+#[derive(Debug)]
+pub struct InvalidHeader(http::Error);
+
+impl<T: Into<http::Error>> From<T> for InvalidHeader {
+    fn from(err: T) -> Self {
+        InvalidHeader(err.into())
+    }
+}
+
+impl fmt::Display for InvalidHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Error for InvalidHeader {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl IntoResponse for InvalidHeader {
+    fn into_response(self) -> Response<Body> {
+        (StatusCode::INTERNAL_SERVER_ERROR, "invalid response header").into_response()
+    }
+}
+
+/// Lets handlers building headers from their own data use `?` on the conversion, instead of
+/// unwrapping it.
+impl IntoResponse for InvalidHeaderValue {
+    fn into_response(self) -> Response<Body> {
+        InvalidHeader::from(self).into_response()
+    }
+}
+
+impl IntoResponse for InvalidHeaderName {
+    fn into_response(self) -> Response<Body> {
+        InvalidHeader::from(self).into_response()
+    }
+}
+
+/// Adds header names to the `Vary` response header, keeping the ones already there, e.g.
+/// `(Vary::new(&["accept-encoding"]), body)`.
+#[derive(Debug, Clone)]
+pub struct Vary(Vec<String>);
+
+impl Vary {
+    pub fn new(headers: &[&str]) -> Self {
+        Vary(headers.iter().map(|header| header.to_string()).collect())
+    }
+}
+
+impl IntoResponseParts for Vary {
+    type Error = InvalidHeader;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        let mut varies = res
+            .headers
+            .get_all(header::VARY)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|name| name.trim().to_ascii_lowercase())
+            .collect::<Vec<_>>();
+
+        for name in self.0 {
+            let name = HeaderName::try_from(name.trim())?;
+            if varies
+                .iter()
+                .any(|vary| vary == "*" || vary == name.as_str())
+            {
+                continue;
+            }
+            res.headers
+                .append(header::VARY, HeaderValue::from_str(name.as_str())?);
+            varies.push(name.as_str().to_owned());
+        }
+
+        Ok(res)
+    }
+}
+
+/// Sets the `Retry-After` header, telling clients when to retry a response like a
+/// `503 Service Unavailable` or a `429 Too Many Requests`, e.g.
+/// `(StatusCode::SERVICE_UNAVAILABLE, RetryAfter::after(Duration::from_secs(120)), body)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryAfter {
+    /// Sent as the number of seconds to wait, like `Retry-After: 120`.
+    Delay(Duration),
+    /// Sent as an HTTP-date, like `Retry-After: Sun, 06 Nov 1994 08:49:37 GMT`.
+    Date(HttpDate),
+}
+
+impl RetryAfter {
+    pub fn after(delay: Duration) -> Self {
+        RetryAfter::Delay(delay)
+    }
+
+    /// Truncates the time to seconds, failing for times before 1970 or after 9999.
+    pub fn at(time: SystemTime) -> Result<Self, DateOutOfRange> {
+        HttpDate::try_from(time).map(RetryAfter::Date)
+    }
+}
+
+impl IntoResponseParts for RetryAfter {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        let value = match self {
+            RetryAfter::Delay(delay) => HeaderValue::from(delay.as_secs()),
+            RetryAfter::Date(date) => date.into(),
+        };
+        res.headers.insert(header::RETRY_AFTER, value);
+        Ok(res)
+    }
+}
+
+/// Inserts the value on the response extensions, e.g. `(Extension(user), body)`, where it can be
+/// read by the layers above the handler.
+impl<T: Clone + Send + Sync + 'static> IntoResponseParts for Extension<T> {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.extensions.insert(self.0);
+        Ok(res)
+    }
+}
+
+/// Sets `Content-Disposition: attachment` so the response is downloaded as `filename`, e.g.
+/// `(Attachment::new("report.csv"), body)`.
+///
+/// Non-ASCII filenames are sent percent-encoded on `filename*` (RFC 5987), with an ASCII-only
+/// `filename` fallback for older clients.
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    filename: String,
+}
+
+impl Attachment {
+    pub fn new(filename: impl Into<String>) -> Self {
+        Attachment {
+            filename: filename.into(),
+        }
+    }
+}
+
+impl IntoResponseParts for Attachment {
+    type Error = InvalidHeader;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        let fallback = self
+            .filename
+            .chars()
+            .map(|ch| match ch {
+                '"' | '\\' => format!("\\{ch}"),
+                ' '..='~' => ch.to_string(),
+                _ => "_".to_owned(),
+            })
+            .collect::<String>();
+
+        let mut value = format!("attachment; filename=\"{fallback}\"");
+
+        if !self.filename.is_ascii() {
+            value.push_str("; filename*=UTF-8''");
+            for byte in self.filename.bytes() {
+                match byte {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => value.push(byte as char),
+                    b'!' | b'#' | b'$' | b'&' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|'
+                    | b'~' => value.push(byte as char),
+                    _ => value.push_str(&format!("%{byte:02X}")),
+                }
+            }
+        }
+
+        res.headers
+            .insert(header::CONTENT_DISPOSITION, HeaderValue::try_from(value)?);
+
+        Ok(res)
+    }
+}
+
+/// Streams the items of an iterator as the chunks of a `text/plain` response, without buffering
+/// the whole body.
+///
+/// Items may be `Result`s, and an `Err` terminates the stream abruptly, so clients can tell the
+/// body is incomplete.
+pub struct StreamText<I>(pub I);
+
+impl<I> IntoResponse for StreamText<I>
+where
+    I: IntoIterator,
+    I::IntoIter: Send + 'static,
+    I::Item: IntoChunk,
+{
+    fn into_response(self) -> Response<Body> {
+        let reader = ChunkReader {
+            chunks: self.0.into_iter(),
+            current: Cursor::new(Vec::new()),
+        };
+        let mut res = Response::builder()
+            .status(StatusCode::OK)
+            .body(Body::from_reader(reader, None))
+            .unwrap();
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain;charset=utf-8"),
+        );
+        res
+    }
+}
+
+/// Items that can be streamed by [`StreamText`].
+pub trait IntoChunk {
+    fn into_chunk(self) -> io::Result<Vec<u8>>;
+}
+
+impl IntoChunk for String {
+    fn into_chunk(self) -> io::Result<Vec<u8>> {
+        Ok(self.into_bytes())
+    }
+}
+
+impl IntoChunk for &'static str {
+    fn into_chunk(self) -> io::Result<Vec<u8>> {
+        Ok(self.as_bytes().to_vec())
+    }
+}
+
+impl IntoChunk for Vec<u8> {
+    fn into_chunk(self) -> io::Result<Vec<u8>> {
+        Ok(self)
+    }
+}
+
+impl<T, E> IntoChunk for Result<T, E>
+where
+    T: IntoChunk,
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    fn into_chunk(self) -> io::Result<Vec<u8>> {
+        self.map_err(io::Error::other)?.into_chunk()
+    }
+}
+
+struct ChunkReader<I> {
+    chunks: I,
+    current: Cursor<Vec<u8>>,
+}
+
+impl<I> Read for ChunkReader<I>
+where
+    I: Iterator,
+    I::Item: IntoChunk,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let read = self.current.read(buf)?;
+            if read > 0 || buf.is_empty() {
+                return Ok(read);
+            }
+            match self.chunks.next() {
+                Some(chunk) => self.current = Cursor::new(chunk.into_chunk()?),
+                None => return Ok(0),
+            }
+        }
+    }
+}
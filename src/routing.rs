@@ -1,13 +1,35 @@
-use std::{convert::Infallible, error::Error};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    convert::Infallible,
+    error::Error,
+    fmt, mem,
+    ops::Add,
+    sync::Arc,
+};
 
-use matchit::Match;
-use touche::{server::Service, Body, Method, Request, Response, StatusCode};
+use matchit::{Match, MatchError};
+use percent_encoding::percent_decode_str;
+use touche::{
+    header,
+    http::{request::Parts as RequestParts, HeaderValue, Uri},
+    server::Service,
+    Body, Connection, HeaderMap, HttpBody, Method, Request, Response, StatusCode,
+};
 
 use crate::{
-    handler::{Handler, HandlerService},
-    response::IntoResponse,
+    extract::{
+        IntoMakeServiceWithConnectInfo, IntoMakeServiceWithTlsInfo, IpCidr, MatchedPath,
+        OriginalUri, TlsInfo, TrustedProxies,
+    },
+    handler::{close, drain_body, drain_request, BodyClaim, Handler, HandlerService},
+    layer::Layer,
+    metrics::Metrics,
+    response::{error_details, IntoResponse},
 };
 
+#[cfg(feature = "json")]
+use crate::response::Problem;
+
 trait RoutedService: Service + Send + Sync {
     fn clone_box(&self) -> Box<dyn RoutedService<Body = Self::Body, Error = Self::Error>>;
 }
@@ -21,10 +43,38 @@ where
     }
 }
 
-pub struct Route<B = Body, E = Infallible> {
+pub struct Route<B = Body, E = Box<dyn Error + Send + Sync>> {
     svc: Box<dyn RoutedService<Body = B, Error = E>>,
 }
 
+impl Route {
+    fn new<S>(svc: S) -> Self
+    where
+        S: Service<Body = Body> + Clone + Send + Sync + 'static,
+        S::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        Route {
+            svc: Box::new(BoxErrorService(svc)),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct BoxErrorService<S>(S);
+
+impl<S> Service for BoxErrorService<S>
+where
+    S: Service<Body = Body>,
+    S::Error: Into<Box<dyn Error + Send + Sync>>,
+{
+    type Body = Body;
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        self.0.call(req).map_err(Into::into)
+    }
+}
+
 impl Clone for Route {
     fn clone(&self) -> Self {
         Route {
@@ -33,7 +83,54 @@ impl Clone for Route {
     }
 }
 
-pub struct MethodRouter<B = Body, E = Infallible> {
+impl Service for Route {
+    type Body = Body;
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        self.svc.call(req)
+    }
+}
+
+const NESTED_PATH_PARAM: &str = "__spike_nested_path";
+
+#[derive(Clone)]
+struct StripPrefix<S>(S);
+
+impl<S: Service<Body = Body>> Service for StripPrefix<S> {
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, mut req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        let nested_path = req
+            .extensions_mut()
+            .get_mut::<Vec<(String, String)>>()
+            .and_then(|params| {
+                let idx = params
+                    .iter()
+                    .position(|(key, _)| key == NESTED_PATH_PARAM)?;
+                Some(params.remove(idx).1)
+            })
+            .unwrap_or_default();
+
+        if req.extensions().get::<OriginalUri>().is_none() {
+            let uri = req.uri().clone();
+            req.extensions_mut().insert(OriginalUri(uri));
+        }
+
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("/{nested_path}?{query}"),
+            None => format!("/{nested_path}"),
+        };
+        let mut uri = req.uri().clone().into_parts();
+        uri.path_and_query = Some(path_and_query.parse().unwrap());
+        *req.uri_mut() = Uri::from_parts(uri).unwrap();
+
+        self.0.call(req)
+    }
+}
+
+pub struct MethodRouter<B = Body, E = Box<dyn Error + Send + Sync>> {
     get: Option<Route<B, E>>,
     post: Option<Route<B, E>>,
     put: Option<Route<B, E>>,
@@ -44,23 +141,346 @@ pub struct MethodRouter<B = Body, E = Infallible> {
     head: Option<Route<B, E>>,
     connect: Option<Route<B, E>>,
     fallback: Option<Route<B, E>>,
+    guards: Vec<(Method, GuardFn, Route<B, E>)>,
+    constraints: Vec<(String, ConstraintFn)>,
+}
+
+type GuardFn = Arc<dyn Fn(&RequestParts) -> bool + Send + Sync>;
+type ConstraintFn = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Error returned when merging [`MethodRouter`]s that define handlers for the same methods.
+#[derive(Debug)]
+pub struct MethodConflict {
+    methods: Vec<&'static str>,
+}
+
+impl MethodConflict {
+    pub fn methods(&self) -> &[&'static str] {
+        &self.methods
+    }
+}
+
+impl fmt::Display for MethodConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting handlers for {}", self.methods.join(", "))
+    }
 }
 
+impl Error for MethodConflict {}
+
 impl MethodRouter {
-    pub fn merge(&mut self, router: MethodRouter) {
+    /// Merges the handlers of `router` into this one, failing without changing anything if both
+    /// define a handler for the same method.
+    pub fn merge(&mut self, router: MethodRouter) -> Result<(), MethodConflict> {
         macro_rules! merge_methods {
-            ($method:ident) => {
-                if self.$method.is_none() && router.$method.is_some() {
-                    self.$method = router.$method;
-                } else if self.$method.is_some() && router.$method.is_some() {
-                    panic!("Method already defined")
+            ($($method:ident => $name:literal),*) => {
+                let methods = [$((self.$method.is_some() && router.$method.is_some(), $name)),*]
+                    .into_iter()
+                    .filter_map(|(conflict, name)| conflict.then_some(name))
+                    .collect::<Vec<_>>();
+
+                if !methods.is_empty() {
+                    return Err(MethodConflict { methods });
                 }
-            };
+
+                $(
+                    if router.$method.is_some() {
+                        self.$method = router.$method;
+                    }
+                )*
+            }
+        }
+
+        merge_methods!(
+            get => "GET",
+            post => "POST",
+            put => "PUT",
+            patch => "PATCH",
+            delete => "DELETE",
+            head => "HEAD",
+            options => "OPTIONS",
+            trace => "TRACE",
+            connect => "CONNECT",
+            fallback => "any"
+        );
+        self.guards.extend(router.guards);
+        self.constraints.extend(router.constraints);
+
+        Ok(())
+    }
+
+    /// Merges the handlers of `router` into this one, calling `resolve` with the existing and the
+    /// incoming handlers of each method defined by both, to pick or combine them.
+    ///
+    /// ```no_run
+    /// # use spike::routing::get;
+    /// # let mut defaults = get(|| "default");
+    /// # let overrides = get(|| "override");
+    /// // Handlers from `overrides` win over the ones from `defaults`.
+    /// defaults.merge_with(overrides, |_existing, incoming| incoming);
+    /// ```
+    pub fn merge_with<F>(&mut self, router: MethodRouter, mut resolve: F)
+    where
+        F: FnMut(Route, Route) -> Route,
+    {
+        macro_rules! merge_methods {
             ($($method:ident),*) => {
-                $(merge_methods!($method);)*
+                $(
+                    self.$method = match (self.$method.take(), router.$method) {
+                        (Some(existing), Some(incoming)) => Some(resolve(existing, incoming)),
+                        (existing, incoming) => existing.or(incoming),
+                    };
+                )*
             }
         }
+
         merge_methods!(get, post, put, patch, delete, head, options, trace, connect, fallback);
+        self.guards.extend(router.guards);
+        self.constraints.extend(router.constraints);
+    }
+
+    /// Wraps every handler of this method router with the `layer`, including the guarded ones, see
+    /// [`Router::route_layer`].
+    pub fn layer<L>(mut self, layer: L) -> MethodRouter
+    where
+        L: Layer<Route>,
+        L::Service: Service<Body = Body> + Clone + Send + Sync + 'static,
+        <L::Service as Service>::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.apply_layer(&|route| Route::new(layer.layer(route)));
+        self
+    }
+
+    fn apply_layer(&mut self, layer: &dyn Fn(Route) -> Route) {
+        macro_rules! layer_methods {
+            ($($method:ident),*) => {
+                $(self.$method = self.$method.take().map(layer);)*
+            }
+        }
+
+        layer_methods!(get, post, put, patch, delete, head, options, trace, connect, fallback);
+        self.guards = self
+            .guards
+            .drain(..)
+            .map(|(method, guard, route)| (method, guard, layer(route)))
+            .collect();
+    }
+
+    /// Wraps the handler and the guards of `method` with the `layer`, leaving the handlers of the
+    /// other methods, and the ones registered after this call, unaffected. `HEAD` requests served
+    /// by the `GET` handler go through the layer too.
+    ///
+    /// ```no_run
+    /// # use spike::{http::Method, layer::HostFilter, routing::get};
+    /// # fn list_posts() -> &'static str { "posts" }
+    /// # fn create_post() -> &'static str { "created" }
+    /// # let auth_layer = HostFilter::new(["example.com"]);
+    /// let route = get(list_posts).post(create_post).layer_method(Method::POST, auth_layer);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no handler nor guard for `method`, so a layer like authentication isn't
+    /// silently skipped.
+    pub fn layer_method<L>(mut self, method: Method, layer: L) -> MethodRouter
+    where
+        L: Layer<Route>,
+        L::Service: Service<Body = Body> + Clone + Send + Sync + 'static,
+        <L::Service as Service>::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let route = match method {
+            Method::GET => &mut self.get,
+            Method::POST => &mut self.post,
+            Method::PUT => &mut self.put,
+            Method::PATCH => &mut self.patch,
+            Method::DELETE => &mut self.delete,
+            Method::OPTIONS => &mut self.options,
+            Method::HEAD => &mut self.head,
+            Method::TRACE => &mut self.trace,
+            Method::CONNECT => &mut self.connect,
+            _ => &mut None,
+        };
+        let mut layered = false;
+        if let Some(inner) = route.take() {
+            *route = Some(Route::new(layer.layer(inner)));
+            layered = true;
+        }
+        self.guards = self
+            .guards
+            .drain(..)
+            .map(|(guarded, guard, route)| match guarded == method {
+                true => {
+                    layered = true;
+                    (guarded, guard, Route::new(layer.layer(route)))
+                }
+                false => (guarded, guard, route),
+            })
+            .collect();
+        if !layered {
+            panic!("no {method} handler to layer");
+        }
+        self
+    }
+}
+
+impl MethodRouter {
+    /// Calls the `handler` for the `method` requests that pass the `predicate`, e.g. to select a
+    /// version of an endpoint by a header. Requests passing no guard are routed by their method as
+    /// usual. Like the method handlers, `GET` guards also serve `HEAD` requests when there is no
+    /// `HEAD` handler, and they are wrapped by [`MethodRouter::layer_method`] for their method.
+    ///
+    /// Guards are evaluated in the order they were added, and the first one passing is used.
+    ///
+    /// ```no_run
+    /// # use spike::{http::{request::Parts as RequestParts, Method}, routing::get};
+    /// # fn show_user() -> &'static str { "v1" }
+    /// # fn show_user_v2() -> &'static str { "v2" }
+    /// fn v2(parts: &RequestParts) -> bool {
+    ///     parts.headers.get("api-version").is_some_and(|version| version == "2")
+    /// }
+    ///
+    /// let route = get(show_user).guard(Method::GET, v2, show_user_v2);
+    /// ```
+    pub fn guard<F, H, T>(mut self, method: Method, predicate: F, handler: H) -> MethodRouter
+    where
+        F: Fn(&RequestParts) -> bool + Send + Sync + 'static,
+        H: Handler<T>,
+        H: Send + Sync,
+        T: 'static,
+    {
+        self.guards.push((
+            method,
+            Arc::new(predicate),
+            Route::new(HandlerService::new(handler)),
+        ));
+        self
+    }
+
+    /// Restricts the values of the `param` path param to the ones passing the `predicate`, e.g.
+    /// `get(show_user).constraint("id", |id| id.parse::<u64>().is_ok())`.
+    ///
+    /// The predicate sees the percent-decoded value, the same as [`Path`](crate::extract::Path)
+    /// gives the handler. Requests with other values, or with values that are not valid UTF-8 once
+    /// decoded, are handled as if the path didn't match, responding with the router's
+    /// `404 Not Found`. As a path matches a single route, no other route is tried.
+    ///
+    /// Registering the route panics if `param` is not a param of its path.
+    pub fn constraint<F>(mut self, param: &str, predicate: F) -> MethodRouter
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        self.constraints
+            .push((param.to_owned(), Arc::new(predicate)));
+        self
+    }
+
+    fn satisfies(&self, params: &[(String, String)]) -> bool {
+        self.constraints.iter().all(|(param, predicate)| {
+            params
+                .iter()
+                .filter(|(name, _)| name == param)
+                .all(|(_, value)| {
+                    percent_decode_str(value)
+                        .decode_utf8()
+                        .is_ok_and(|value| predicate(&value))
+                })
+        })
+    }
+
+    fn dispatch(
+        &self,
+        mut req: Request<Body>,
+        automatic_options: bool,
+        error_responses: &ErrorResponses,
+    ) -> Response<Body> {
+        if !self.guards.is_empty() {
+            let head_as_get = req.method() == Method::HEAD && self.head.is_none();
+            let (parts, body) = req.into_parts();
+            let guarded = self.guards.iter().find(|(method, guard, _)| {
+                (*method == parts.method || head_as_get && method == Method::GET) && guard(&parts)
+            });
+            req = Request::from_parts(parts, body);
+            if let Some((_, _, route)) = guarded {
+                return route
+                    .svc
+                    .call(req)
+                    .unwrap_or_else(|err| error_responses.service_error(err));
+            }
+        }
+
+        let route = match *req.method() {
+            Method::GET => self.get.as_ref(),
+            Method::POST => self.post.as_ref(),
+            Method::PUT => self.put.as_ref(),
+            Method::PATCH => self.patch.as_ref(),
+            Method::DELETE => self.delete.as_ref(),
+            // Without a HEAD handler, the GET one is called with the same request, so the params
+            // and extensions inserted by the router are there, and the request is extracted once.
+            Method::HEAD => self.head.as_ref().or(self.get.as_ref()),
+            Method::OPTIONS => self.options.as_ref(),
+            Method::TRACE => self.trace.as_ref(),
+            Method::CONNECT => self.connect.as_ref(),
+            _ => None,
+        };
+
+        match route.or(self.fallback.as_ref()) {
+            Some(route) => route
+                .svc
+                .call(req)
+                .unwrap_or_else(|err| error_responses.service_error(err)),
+            None if req.method() == Method::OPTIONS && automatic_options => {
+                let mut headers = HeaderMap::new();
+                headers.insert(
+                    header::ALLOW,
+                    HeaderValue::from_str(&self.allowed_methods().join(", ")).unwrap(),
+                );
+                drain_request(req, (headers, StatusCode::NO_CONTENT).into_response())
+            }
+            None => drain_request(req, error_responses.method_not_allowed()),
+        }
+    }
+
+    fn allowed_methods(&self) -> Vec<&'static str> {
+        let guarded = |method| self.guards.iter().any(|(m, _, _)| *m == method);
+        let get = self.get.is_some() || guarded(Method::GET);
+        [
+            (get, "GET"),
+            (self.head.is_some() || guarded(Method::HEAD) || get, "HEAD"),
+            (self.post.is_some() || guarded(Method::POST), "POST"),
+            (self.put.is_some() || guarded(Method::PUT), "PUT"),
+            (self.patch.is_some() || guarded(Method::PATCH), "PATCH"),
+            (self.delete.is_some() || guarded(Method::DELETE), "DELETE"),
+            (true, "OPTIONS"),
+            (self.trace.is_some() || guarded(Method::TRACE), "TRACE"),
+            (
+                self.connect.is_some() || guarded(Method::CONNECT),
+                "CONNECT",
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(allowed, name)| allowed.then_some(name))
+        .collect()
+    }
+
+    fn override_with(&mut self, router: MethodRouter) {
+        self.merge_with(router, |_existing, incoming| incoming);
+    }
+}
+
+/// Merges two method routers.
+///
+/// # Panics
+///
+/// Panics if both define a handler for the same method, see [`MethodRouter::merge`] for a
+/// fallible alternative.
+impl Add for MethodRouter {
+    type Output = MethodRouter;
+
+    fn add(mut self, router: MethodRouter) -> MethodRouter {
+        if let Err(err) = self.merge(router) {
+            panic!("{err}");
+        }
+        self
     }
 }
 
@@ -71,12 +491,10 @@ macro_rules! impl_method_router_methods {
             where
                 H: Handler<T>,
                 H: Send + Sync,
-                T: Send + Sync + Clone + 'static,
+                T: 'static,
             {
                 Self {
-                    $method: Some(Route {
-                        svc: Box::new(HandlerService::new(handler)),
-                    }),
+                    $method: Some(Route::new(HandlerService::new(handler))),
                     ..self
                 }
             }
@@ -93,12 +511,10 @@ macro_rules! impl_router_methods {
         where
             H: Handler<T>,
             H: Send + Sync,
-            T: Send + Sync + Clone + 'static,
+            T: 'static,
         {
             MethodRouter {
-                $method: Some(Route {
-                    svc: Box::new(HandlerService::new(handler)),
-                }),
+                $method: Some(Route::new(HandlerService::new(handler))),
                 ..Default::default()
             }
         }
@@ -116,12 +532,10 @@ impl MethodRouter {
     where
         H: Handler<T>,
         H: Send + Sync,
-        T: Send + Sync + Clone + 'static,
+        T: 'static,
     {
         MethodRouter {
-            fallback: Some(Route {
-                svc: Box::new(HandlerService::new(handler)),
-            }),
+            fallback: Some(Route::new(HandlerService::new(handler))),
             ..self
         }
     }
@@ -131,12 +545,10 @@ pub fn any<H, T>(handler: H) -> MethodRouter
 where
     H: Handler<T>,
     H: Send + Sync,
-    T: Send + Sync + Clone + 'static,
+    T: 'static,
 {
     MethodRouter {
-        fallback: Some(Route {
-            svc: Box::new(HandlerService::new(handler)),
-        }),
+        fallback: Some(Route::new(HandlerService::new(handler))),
         ..Default::default()
     }
 }
@@ -154,6 +566,8 @@ impl Default for MethodRouter {
             head: None,
             connect: None,
             fallback: None,
+            guards: Vec::new(),
+            constraints: Vec::new(),
         }
     }
 }
@@ -171,38 +585,664 @@ impl Clone for MethodRouter {
             head: self.head.clone(),
             connect: self.connect.clone(),
             fallback: self.fallback.clone(),
+            guards: self.guards.clone(),
+            constraints: self.constraints.clone(),
         }
     }
 }
 
-#[derive(Clone, Default)]
+/// What the [`Router`] does with `GET`, `HEAD` and `DELETE` requests that carry a body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BodyPolicy {
+    /// Hands the body to the handler, as with any other method.
+    #[default]
+    Allow,
+    /// Responds with `400 Bad Request`.
+    Reject,
+    /// Discards the body before calling the handler. Chunked bodies are drained up to a limit, and
+    /// past it the connection is closed after responding.
+    Ignore,
+}
+
+/// Two routes that match some of the same paths, as reported by [`Router::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteOverlap {
+    /// The route used on the paths matched by both.
+    pub preferred: String,
+    /// The route that is not reachable on those paths.
+    pub shadowed: String,
+}
+
+impl fmt::Display for RouteOverlap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "route `{}` takes precedence over `{}`",
+            self.preferred, self.shadowed
+        )
+    }
+}
+
+/// Routes requests by their path and method. The HTTP version of the requests plays no part, and
+/// can be read by handlers with the [`Version`](touche::http::Version) extractor.
+#[derive(Clone)]
 pub struct Router {
-    router: matchit::Router<MethodRouter>,
+    router: Arc<matchit::Router<(Arc<str>, MethodRouter)>>,
+    paths: Arc<Vec<String>>,
+    trusted_proxies: Option<TrustedProxies>,
+    body_policy: BodyPolicy,
+    automatic_options: bool,
+    case_insensitive: bool,
+    normalize_path: bool,
+    redirect_trailing_slash: bool,
+    error_responses: ErrorResponses,
+    // The `before` layers, built once around the `Routes` service.
+    before: Option<Arc<Route>>,
+    on_response: Option<OnResponseFn>,
+    metrics: Metrics,
 }
 
+type OnResponseFn = Arc<dyn Fn(&RequestParts, &Response<Body>) + Send + Sync>;
+
 impl Router {
     pub fn new() -> Self {
         Self {
-            router: matchit::Router::new(),
+            router: Arc::new(matchit::Router::new()),
+            paths: Arc::new(Vec::new()),
+            trusted_proxies: None,
+            body_policy: BodyPolicy::Allow,
+            automatic_options: true,
+            case_insensitive: false,
+            normalize_path: false,
+            redirect_trailing_slash: false,
+            error_responses: ErrorResponses::default(),
+            before: None,
+            on_response: None,
+            metrics: Metrics::default(),
         }
     }
 }
 
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Router {
+    /// Registers the `route` on `path`, merging it with any route previously registered there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path is invalid, or if it already has a handler for one of the methods.
     pub fn route(mut self, path: &str, route: MethodRouter) -> Router {
-        match self.router.at_mut(path) {
-            Ok(Match {
-                value: existing_route,
+        self.route_mut(path, route);
+        self
+    }
+
+    /// Same as [`Router::route`], but through a mutable reference, for building a router
+    /// imperatively, like in a loop.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn route_mut(&mut self, path: &str, route: MethodRouter) -> &mut Router {
+        self.insert_route(path, route, |existing, route| {
+            if let Err(err) = existing.merge(route) {
+                panic!("{err} on route `{path}`");
+            }
+        });
+        self
+    }
+
+    /// Builds a router with `routes`, like calling [`Router::route`] for each of them, but
+    /// inserting each path on the route table only once, which is faster for large tables.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn from_routes<I, P>(routes: I) -> Router
+    where
+        I: IntoIterator<Item = (P, MethodRouter)>,
+        P: AsRef<str>,
+    {
+        let mut paths = Vec::new();
+        let mut method_routers: Vec<MethodRouter> = Vec::new();
+        let mut indexes: HashMap<String, usize> = HashMap::new();
+
+        for (path, route) in routes {
+            match indexes.entry(path.as_ref().to_owned()) {
+                Entry::Occupied(entry) => {
+                    if let Err(err) = method_routers[*entry.get()].merge(route) {
+                        panic!("{err} on route `{}`", entry.key());
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    paths.push(entry.key().clone());
+                    method_routers.push(route);
+                    entry.insert(paths.len() - 1);
+                }
+            }
+        }
+
+        let mut router = matchit::Router::new();
+        for (path, route) in paths.iter().zip(method_routers) {
+            router
+                .insert(path.as_str(), (Arc::from(path.as_str()), route))
+                .unwrap();
+        }
+
+        Self {
+            router: Arc::new(router),
+            paths: Arc::new(paths),
+            ..Self::new()
+        }
+    }
+
+    /// Registers the same `route` on each of the `paths`, e.g. `&["/health", "/healthz"]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn route_aliases(self, paths: &[&str], route: MethodRouter) -> Router {
+        paths
+            .iter()
+            .fold(self, |router, path| router.route(path, route.clone()))
+    }
+
+    /// Registers the `route` on `path`, replacing the handlers previously registered there for
+    /// the same methods.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the path is invalid.
+    pub fn route_override(mut self, path: &str, route: MethodRouter) -> Router {
+        self.insert_route(path, route, MethodRouter::override_with);
+        self
+    }
+
+    /// Registers the `service` on `path` for every method. The service sees the full path of the
+    /// request, so another [`Router`] can be mounted on a catch-all path like `/api/*rest`.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn route_service<S>(self, path: &str, service: S) -> Router
+    where
+        S: Service<Body = Body> + Clone + Send + Sync + 'static,
+        S::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let route = MethodRouter {
+            fallback: Some(Route::new(service)),
+            ..Default::default()
+        };
+        self.route(path, route)
+    }
+
+    /// Registers the `service` on every path under `prefix`, and on `prefix` itself, for every
+    /// method.
+    ///
+    /// The prefix is stripped from the path before calling the service, so a service nested on
+    /// `/static` sees `/static/css/app.css` as `/css/app.css`, and both `/static` and `/static/` as
+    /// `/`. The query string is kept, and the original URI can be extracted with
+    /// [`OriginalUri`](crate::extract::OriginalUri).
+    ///
+    /// A service nested on `/` sees the paths unchanged, and gets the requests not matching the
+    /// other routes of this router, merging another [`Router`] into this one.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn nest_service<S>(mut self, prefix: &str, service: S) -> Router
+    where
+        S: Service<Body = Body> + Clone + Send + Sync + 'static,
+        S::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.nest_service_mut(prefix, service);
+        self
+    }
+
+    /// Same as [`Router::nest_service`], but through a mutable reference.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn nest_service_mut<S>(&mut self, prefix: &str, service: S) -> &mut Router
+    where
+        S: Service<Body = Body> + Clone + Send + Sync + 'static,
+        S::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let prefix = prefix.trim_end_matches('/');
+        let route = MethodRouter {
+            fallback: Some(Route::new(StripPrefix(service))),
+            ..Default::default()
+        };
+        if !prefix.is_empty() {
+            self.route_mut(prefix, route.clone());
+        }
+        self.route_mut(&format!("{prefix}/"), route.clone())
+            .route_mut(&format!("{prefix}/*{NESTED_PATH_PARAM}"), route)
+    }
+
+    /// Registers a `GET` handler on `path` that always responds with `200 OK` and an `ok` body.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn health_check(self, path: &str) -> Router {
+        self.route(path, get(|| "ok"))
+    }
+
+    /// Registers a `GET` handler on `path` that responds with `200 OK` when `ready` returns
+    /// `true`, and `503 Service Unavailable` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics on the same cases as [`Router::route`].
+    pub fn readiness_check<F>(self, path: &str, ready: F) -> Router
+    where
+        F: Fn() -> bool + Clone + Send + Sync + 'static,
+    {
+        self.route(
+            path,
+            get(move || {
+                if ready() {
+                    (StatusCode::OK, "ok")
+                } else {
+                    (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+                }
+            }),
+        )
+    }
+
+    /// Registers the `route` on `path` both with and without its last segment, which must be a
+    /// param, so `/posts/:id` also matches `/posts`. Use [`Path<Option<T>>`](crate::extract::Path)
+    /// to extract the param.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the last segment of the path is not a param, or on the same cases as
+    /// [`Router::route`].
+    pub fn with_trailing_param_optional(self, path: &str, route: MethodRouter) -> Router {
+        let (prefix, last) = path.rsplit_once('/').unwrap_or_default();
+        if !last.starts_with(':') {
+            panic!("last segment of route `{path}` is not a param");
+        }
+        let prefix = if prefix.is_empty() { "/" } else { prefix };
+        self.route(prefix, route.clone()).route(path, route)
+    }
+
+    /// Returns the path templates of the registered routes, in registration order.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.paths.iter().map(String::as_str)
+    }
+
+    /// Returns the names of the params of the route registered on exactly the `path` template, in
+    /// the order they are extracted by [`Path`](crate::extract::Path), or `None` if there isn't
+    /// one. `/users/:id/posts/*rest` has the `["id", "rest"]` params.
+    pub fn param_names(&self, path: &str) -> Option<Vec<&str>> {
+        let path = self.paths.iter().find(|registered| *registered == path)?;
+        Some(
+            path.split('/')
+                .filter_map(|segment| {
+                    segment
+                        .strip_prefix(':')
+                        .or_else(|| segment.strip_prefix('*'))
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the path template of the route matching a request to `path`, like `/files/:name`
+    /// for `/files/report.pdf`, or `None` if no route matches.
+    ///
+    /// Useful to check which route wins among overlapping ones: static segments take precedence
+    /// over params, so with `/files/config` also registered, `/files/config` matches it.
+    pub fn which_route(&self, path: &str) -> Option<&str> {
+        let path = if self.case_insensitive {
+            path.to_ascii_lowercase()
+        } else {
+            path.to_owned()
+        };
+        let (template, _) = self.router.at(&path).ok()?.value;
+        Some(template)
+    }
+
+    /// Replaces the method router registered on exactly the `path` template, like `/users/:id`,
+    /// with the one returned by `update`, which is given the current one. Returns `false`, without
+    /// calling `update`, if there isn't one.
+    ///
+    /// ```no_run
+    /// # use spike::{routing::get, Router};
+    /// # let admin = true;
+    /// let mut router = Router::new().route("/users/:id", get(|| "user"));
+    /// if admin {
+    ///     router.update_method_router("/users/:id", |route| route.delete(|| "deleted"));
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics like [`route`](Router::route) does if the new method router has a
+    /// [constraint](MethodRouter::constraint) on a param the path doesn't have.
+    pub fn update_method_router<F>(&mut self, path: &str, update: F) -> bool
+    where
+        F: FnOnce(MethodRouter) -> MethodRouter,
+    {
+        if !self.paths.iter().any(|registered| registered == path) {
+            return false;
+        }
+        let route = &mut Arc::make_mut(&mut self.router)
+            .at_mut(path)
+            .unwrap()
+            .value
+            .1;
+        let updated = update(mem::take(route));
+        check_constraints(path, &updated);
+        *route = updated;
+        true
+    }
+
+    fn insert_route<F>(&mut self, path: &str, route: MethodRouter, merge: F)
+    where
+        F: FnOnce(&mut MethodRouter, MethodRouter),
+    {
+        check_constraints(path, &route);
+
+        let router = Arc::make_mut(&mut self.router);
+        if self.paths.iter().any(|registered| registered == path) {
+            let Match {
+                value: (_, existing_route),
                 ..
-            }) => {
-                existing_route.merge(route);
+            } = router.at_mut(path).unwrap();
+            merge(existing_route, route);
+        } else {
+            router.insert(path, (Arc::from(path), route)).unwrap();
+            Arc::make_mut(&mut self.paths).push(path.to_owned());
+        }
+    }
+}
+
+// Panics if the `route` has a constraint on a param the `path` doesn't have.
+fn check_constraints(path: &str, route: &MethodRouter) {
+    for (param, _) in &route.constraints {
+        if !path_params(path).any(|name| name == param) {
+            panic!("constraint on `{param}`, which is not a param of route `{path}`");
+        }
+    }
+}
+
+// The names of the `:name` and `*name` params of a route path.
+fn path_params(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter_map(|segment| {
+        let start = segment.find([':', '*'])?;
+        Some(&segment[start + 1..])
+    })
+}
+
+impl Router {
+    /// Sets the proxies, as IPs or CIDR ranges, whose forwarding headers are trusted by the
+    /// [`ClientIp`](crate::extract::ClientIp) extractor.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the proxies is not a valid IP or CIDR range.
+    pub fn trusted_proxies<I, P>(self, proxies: I) -> Router
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<str>,
+    {
+        let proxies = proxies
+            .into_iter()
+            .map(|proxy| {
+                proxy
+                    .as_ref()
+                    .parse::<IpCidr>()
+                    .unwrap_or_else(|err| panic!("{err}"))
+            })
+            .collect();
+
+        Self {
+            trusted_proxies: Some(TrustedProxies::new(proxies)),
+            ..self
+        }
+    }
+
+    /// Sets how requests that aren't supposed to carry a body are handled when they do. Defaults
+    /// to [`BodyPolicy::Allow`].
+    pub fn body_policy(self, body_policy: BodyPolicy) -> Router {
+        Self {
+            body_policy,
+            ..self
+        }
+    }
+
+    /// Lists the registered routes that match some of the same paths, like `/users/me` and
+    /// `/users/:id`, with the one that takes precedence on those paths.
+    pub fn check(&self) -> Vec<RouteOverlap> {
+        let mut overlaps = Vec::new();
+        for (idx, path) in self.paths.iter().enumerate() {
+            for other in &self.paths[idx + 1..] {
+                let segments = path.split('/').map(Segment::from).collect::<Vec<_>>();
+                let other_segments = other.split('/').map(Segment::from).collect::<Vec<_>>();
+                if !segments_overlap(&segments, &other_segments) {
+                    continue;
+                }
+                let (preferred, shadowed) = if segments < other_segments {
+                    (path, other)
+                } else {
+                    (other, path)
+                };
+                overlaps.push(RouteOverlap {
+                    preferred: preferred.clone(),
+                    shadowed: shadowed.clone(),
+                });
             }
-            _ => {
-                self.router.insert(path, route).unwrap();
+        }
+        overlaps
+    }
+
+    /// Sets whether `OPTIONS` requests to routes without an `options` handler are answered with a
+    /// `204 No Content` listing the allowed methods on the `Allow` header. Enabled by default,
+    /// when disabled these requests get a `405 Method Not Allowed`.
+    pub fn automatic_options(self, automatic_options: bool) -> Router {
+        Self {
+            automatic_options,
+            ..self
+        }
+    }
+
+    /// Sets whether paths are matched ignoring the case of ASCII letters, for clients that send
+    /// mixed-case paths. Disabled by default.
+    ///
+    /// The request path is lowercased before matching, so the static segments of the registered
+    /// routes must be lowercase. The values of path params keep the case they were sent with.
+    pub fn case_insensitive(self, case_insensitive: bool) -> Router {
+        Self {
+            case_insensitive,
+            ..self
+        }
+    }
+
+    /// Sets whether consecutive slashes on the request path are collapsed before matching, so
+    /// `/users//42` is routed as `/users/42`. A trailing slash is kept. Disabled by default.
+    ///
+    /// Handlers see the normalized path, and so do the path params, while the path as sent is kept
+    /// on [`OriginalUri`](crate::extract::OriginalUri).
+    pub fn normalize_path(self, normalize_path: bool) -> Router {
+        Self {
+            normalize_path,
+            ..self
+        }
+    }
+
+    /// Sets whether requests whose path only matches a route after adding or removing a trailing
+    /// slash are redirected there, with a `308 Permanent Redirect`. Disabled by default.
+    ///
+    /// The redirect is sent whatever the method of the request, and the route is only checked for
+    /// a handler of the method when the client follows it, as the `308` keeps the method. So a
+    /// `POST /users/` gets redirected to `/users` even if that route has no `POST` handler, and only
+    /// then gets a `405 Method Not Allowed`.
+    pub fn redirect_trailing_slash(self, redirect_trailing_slash: bool) -> Router {
+        Self {
+            redirect_trailing_slash,
+            ..self
+        }
+    }
+
+    /// Replaces the response built by the router itself for `status`, which must be
+    /// `404 Not Found` (no route matches), `405 Method Not Allowed` (the route has no handler for
+    /// the method) or `500 Internal Server Error` (a service failed). The response is cloned for
+    /// each use, and always keeps `status`.
+    ///
+    /// Routers nested in this one, like with [`Router::nest_service`], use its `404 Not Found`
+    /// response when they don't set their own, so the nested one takes precedence, then the
+    /// closest outer one, then the built-in response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` is not one of the above.
+    pub fn default_error_response<R>(mut self, status: StatusCode, response: R) -> Router
+    where
+        R: IntoResponse + Clone + Send + Sync + 'static,
+    {
+        let response: ResponseFn = Arc::new(move || {
+            let mut res = response.clone().into_response();
+            *res.status_mut() = status;
+            res
+        });
+        match status {
+            StatusCode::NOT_FOUND => self.error_responses.not_found = Some(response),
+            StatusCode::METHOD_NOT_ALLOWED => {
+                self.error_responses.method_not_allowed = Some(response)
+            }
+            StatusCode::INTERNAL_SERVER_ERROR => {
+                self.error_responses.internal_server_error = Some(response)
             }
+            status => panic!("{status} is not a default error response of the router"),
+        }
+        self
+    }
+
+    /// Wraps the whole router with the `layer`, which runs on every request before it is routed,
+    /// so it can respond by itself without reaching the routing table, e.g. with a
+    /// `503 Service Unavailable` during maintenance.
+    ///
+    /// Unlike a layer applied to a route, it also runs on requests that wouldn't match any route,
+    /// and it can't see the path params. Layers wrap the ones added before them, so the last one
+    /// added runs first.
+    pub fn before<L>(mut self, layer: L) -> Router
+    where
+        L: Layer<Route> + Send + Sync + 'static,
+        L::Service: Service<Body = Body> + Clone + Send + Sync + 'static,
+        <L::Service as Service>::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let inner = match self.before.take() {
+            Some(route) => Arc::unwrap_or_clone(route),
+            None => Route::new(Routes),
+        };
+        self.before = Some(Arc::new(Route::new(layer.layer(inner))));
+        self
+    }
+
+    /// Wraps the handlers of the routes registered so far with the `layer`.
+    ///
+    /// Unlike the [`before`](Router::before) layers, it runs after the request is routed, so it
+    /// can read the [`MatchedPath`](crate::extract::MatchedPath) and the path params, e.g. to
+    /// record metrics per route. It doesn't run on requests that match no route, or whose route
+    /// has no handler for their method.
+    ///
+    /// Layers wrap the ones added before them, including the ones added with
+    /// [`MethodRouter::layer`], so the last one added runs first, after all the `before` layers.
+    pub fn route_layer<L>(mut self, layer: L) -> Router
+    where
+        L: Layer<Route>,
+        L::Service: Service<Body = Body> + Clone + Send + Sync + 'static,
+        <L::Service as Service>::Error: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let router = Arc::make_mut(&mut self.router);
+        for path in self.paths.iter() {
+            let (_, route) = router.at_mut(path).unwrap().value;
+            route.apply_layer(&|route| Route::new(layer.layer(route)));
+        }
+        self
+    }
+
+    /// Calls `callback` with the request and the response after every request, including the ones
+    /// answered by the router itself, like a `404 Not Found`, or by its [`before`](Router::before)
+    /// layers, e.g. to record custom metrics without writing a layer.
+    ///
+    /// The request parts have the method, URI, version and headers of the request, but no
+    /// extensions.
+    pub fn on_response<F>(self, callback: F) -> Router
+    where
+        F: Fn(&RequestParts, &Response<Body>) + Send + Sync + 'static,
+    {
+        Self {
+            on_response: Some(Arc::new(callback)),
+            ..self
         }
+    }
+
+    /// Sets whether the responses built by the router itself, like the `404 Not Found` when no
+    /// route matches, are rendered as [`Problem`] details. Disabled by default, and overridden by
+    /// [`Router::default_error_response`].
+    #[cfg(feature = "json")]
+    pub fn problem_details(mut self, problem_details: bool) -> Router {
+        self.error_responses.problem_details = problem_details;
         self
     }
+
+    /// Sets whether error responses describe their cause, like the deserialization error of a
+    /// [`Json`](crate::Json) rejection or the error returned by a handler, which may reveal
+    /// internals of the application. Enabled by default on debug builds only.
+    pub fn error_details(mut self, error_details: bool) -> Router {
+        self.error_responses.error_details = error_details;
+        self
+    }
+
+    /// Returns a handle to the request and response counters of this router.
+    pub fn metrics_handle(&self) -> Metrics {
+        self.metrics.clone()
+    }
+
+    /// Converts the router into a [`MakeService`](touche::server::MakeService) that exposes the
+    /// [`ConnectInfo`](crate::extract::ConnectInfo) of each request.
+    pub fn into_make_service_with_connect_info(self) -> IntoMakeServiceWithConnectInfo<Self> {
+        IntoMakeServiceWithConnectInfo(self)
+    }
+
+    /// Converts the router into a [`MakeService`](touche::server::MakeService) that exposes the
+    /// [`TlsInfo`] of each request, as returned by `tls_info` for its connection.
+    ///
+    /// TLS is terminated before the connections reach the server, and touche doesn't expose their
+    /// sessions, so the code accepting the connections must record the details of each handshake,
+    /// like on a map keyed by the peer address, for `tls_info` to look them up. Connections
+    /// without TLS info are plaintext for the [`TlsInfo`] extractor.
+    pub fn into_make_service_with_tls_info<F>(
+        self,
+        tls_info: F,
+    ) -> IntoMakeServiceWithTlsInfo<Self, F>
+    where
+        F: Fn(&Connection) -> Option<TlsInfo> + Clone,
+    {
+        IntoMakeServiceWithTlsInfo {
+            svc: self,
+            tls_info,
+        }
+    }
+}
+
+/// Serves the method router on any path, so a single endpoint server doesn't need a [`Router`].
+impl Service for MethodRouter {
+    type Body = Body;
+    type Error = Infallible;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        let error_responses = ErrorResponses::default();
+        let res = self.dispatch(req, true, &error_responses);
+        Ok(error_details(res, error_responses.error_details))
+    }
 }
 
 impl Service for Router {
@@ -211,51 +1251,298 @@ impl Service for Router {
     type Error = Box<dyn Error + Send + Sync>;
 
     fn call(&self, mut req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
-        match self.router.at(req.uri().path()) {
+        self.metrics.record_request(req.body().len());
+        let claim = (req.body().len().is_none() && req.extensions().get::<BodyClaim>().is_none())
+            .then(|| {
+                let claim = BodyClaim::default();
+                req.extensions_mut().insert(claim.clone());
+                claim
+            });
+        let parts = self.on_response.as_ref().map(|_| {
+            let (mut parts, ()) = Request::new(()).into_parts();
+            parts.method = req.method().clone();
+            parts.uri = req.uri().clone();
+            parts.version = req.version();
+            parts.headers = req.headers().clone();
+            parts
+        });
+        let res = match &self.before {
+            Some(before) => {
+                req.extensions_mut().insert(RoutesOf(self.clone()));
+                before.call(req)
+            }
+            None => self.dispatch(req),
+        };
+        let res = res.unwrap_or_else(|err| self.error_responses.service_error(err));
+        let res = error_details(res, self.error_responses.error_details);
+        let res = match claim {
+            Some(claim) if !claim.is_claimed() => close(res),
+            _ => res,
+        };
+        self.metrics.record_response(res.status(), res.body().len());
+        if let (Some(on_response), Some(parts)) = (&self.on_response, parts) {
+            on_response(&parts, &res);
+        }
+        Ok(res)
+    }
+}
+
+// The innermost service of the `before` layers, routing the request with the router it carries,
+// so the layers don't capture the routes registered when they were added.
+#[derive(Clone)]
+struct Routes;
+
+struct RoutesOf(Router);
+
+impl Service for Routes {
+    type Body = Body;
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn call(&self, mut req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        match req.extensions_mut().remove::<RoutesOf>() {
+            Some(RoutesOf(router)) => router.dispatch(req),
+            None => Err("a before layer dropped the request extensions".into()),
+        }
+    }
+}
+
+impl Router {
+    fn dispatch(
+        &self,
+        mut req: Request<Body>,
+    ) -> Result<Response<Body>, Box<dyn Error + Send + Sync>> {
+        if self.body_policy != BodyPolicy::Allow
+            && matches!(*req.method(), Method::GET | Method::HEAD | Method::DELETE)
+            && has_body(&req)
+        {
+            match self.body_policy {
+                BodyPolicy::Reject => {
+                    let res = self.error_responses.status(StatusCode::BAD_REQUEST);
+                    return Ok(drain_request(req, res));
+                }
+                _ => {
+                    req.headers_mut().remove(header::CONTENT_LENGTH);
+                    req.headers_mut().remove(header::TRANSFER_ENCODING);
+                    if !drain_body(mem::replace(req.body_mut(), Body::empty())) {
+                        BodyClaim::close(req.extensions());
+                    }
+                }
+            }
+        }
+
+        if self.normalize_path && req.uri().path().contains("//") {
+            if req.extensions().get::<OriginalUri>().is_none() {
+                let uri = req.uri().clone();
+                req.extensions_mut().insert(OriginalUri(uri));
+            }
+
+            let mut path_and_query = String::with_capacity(req.uri().path().len());
+            for ch in req.uri().path().chars() {
+                if !(ch == '/' && path_and_query.ends_with('/')) {
+                    path_and_query.push(ch);
+                }
+            }
+            if let Some(query) = req.uri().query() {
+                path_and_query.push('?');
+                path_and_query.push_str(query);
+            }
+            let mut uri = req.uri().clone().into_parts();
+            uri.path_and_query = Some(path_and_query.parse().unwrap());
+            *req.uri_mut() = Uri::from_parts(uri).unwrap();
+        }
+
+        let path = req.uri().path();
+        let lowercase_path;
+        let lookup_path = if self.case_insensitive {
+            lowercase_path = path.to_ascii_lowercase();
+            &lowercase_path
+        } else {
+            path
+        };
+
+        match self.router.at(lookup_path) {
             Ok(Match {
-                value: route,
+                value: (matched_path, route),
                 params,
             }) => {
                 let params = params
                     .iter()
-                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                    .map(|(k, v)| {
+                        // Param values are slices of the lookup path, and ASCII lowercasing keeps
+                        // the offsets, so their original case is taken from the request path.
+                        let start = v.as_ptr() as usize - lookup_path.as_ptr() as usize;
+                        (k.to_owned(), path[start..start + v.len()].to_owned())
+                    })
                     .collect::<Vec<_>>();
+                if !route.satisfies(&params) {
+                    return Ok(self.not_found(req));
+                }
                 req.extensions_mut().insert(params);
-                match *req.method() {
-                    Method::GET if route.get.is_some() => {
-                        Ok(route.get.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::POST if route.post.is_some() => {
-                        Ok(route.post.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::PUT if route.put.is_some() => {
-                        Ok(route.put.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::PATCH if route.patch.is_some() => {
-                        Ok(route.patch.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::DELETE if route.delete.is_some() => {
-                        Ok(route.delete.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::HEAD if route.head.is_some() => {
-                        Ok(route.head.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::OPTIONS if route.options.is_some() => {
-                        Ok(route.options.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::TRACE if route.trace.is_some() => {
-                        Ok(route.trace.clone().unwrap().svc.call(req)?)
-                    }
-                    Method::CONNECT if route.connect.is_some() => {
-                        Ok(route.connect.clone().unwrap().svc.call(req)?)
-                    }
-                    _ if route.fallback.is_some() => {
-                        Ok(route.fallback.clone().unwrap().svc.call(req)?)
-                    }
-                    _ => Ok(StatusCode::METHOD_NOT_ALLOWED.into_response()),
+                req.extensions_mut()
+                    .insert(MatchedPath(matched_path.clone()));
+                if let Some(proxies) = &self.trusted_proxies {
+                    req.extensions_mut().insert(proxies.clone());
+                }
+                if let Some(not_found) = &self.error_responses.not_found {
+                    req.extensions_mut()
+                        .insert(OuterNotFound(not_found.clone()));
+                }
+                Ok(route.dispatch(req, self.automatic_options, &self.error_responses))
+            }
+            Err(MatchError::NotFound) => Ok(self.not_found(req)),
+            Err(_) if !self.redirect_trailing_slash => Ok(self.not_found(req)),
+            Err(_) => {
+                let fixed_path = match lookup_path.strip_suffix('/') {
+                    Some(path) => path.trim_end_matches('/').to_owned(),
+                    None => format!("{lookup_path}/"),
+                };
+                // matchit can suggest a fix that doesn't match either.
+                if fixed_path.is_empty() || self.router.at(&fixed_path).is_err() {
+                    return Ok(self.not_found(req));
+                }
+
+                // The location is built from the path as sent, keeping its case, and the prefix
+                // of nested routers.
+                let original_path = req
+                    .extensions()
+                    .get::<OriginalUri>()
+                    .map_or(path, |OriginalUri(uri)| uri.path());
+                let mut location = match original_path.strip_suffix('/') {
+                    Some(path) => path.trim_end_matches('/').to_owned(),
+                    None => format!("{original_path}/"),
+                };
+                // A location starting with `//`, or with `/\`, which browsers read the same way,
+                // would be taken as the authority of another host.
+                location = format!("/{}", location.trim_start_matches('/')).replace('\\', "%5C");
+                if let Some(query) = req.uri().query() {
+                    location.push('?');
+                    location.push_str(query);
+                }
+
+                let mut headers = HeaderMap::new();
+                headers.insert(header::LOCATION, HeaderValue::try_from(location)?);
+                let res = (headers, StatusCode::PERMANENT_REDIRECT).into_response();
+                Ok(drain_request(req, res))
+            }
+        }
+    }
+
+    fn not_found(&self, req: Request<Body>) -> Response<Body> {
+        let res = self.error_responses.not_found(&req);
+        drain_request(req, res)
+    }
+}
+
+type ResponseFn = Arc<dyn Fn() -> Response<Body> + Send + Sync>;
+
+// Responses set with `Router::default_error_response`, replacing the built-in ones.
+#[derive(Clone)]
+struct ErrorResponses {
+    not_found: Option<ResponseFn>,
+    method_not_allowed: Option<ResponseFn>,
+    internal_server_error: Option<ResponseFn>,
+    #[cfg(feature = "json")]
+    problem_details: bool,
+    error_details: bool,
+}
+
+impl Default for ErrorResponses {
+    fn default() -> Self {
+        Self {
+            not_found: None,
+            method_not_allowed: None,
+            internal_server_error: None,
+            #[cfg(feature = "json")]
+            problem_details: false,
+            error_details: cfg!(debug_assertions),
+        }
+    }
+}
+
+// The custom not found response of the closest router that routed the request to a nested one.
+#[derive(Clone)]
+struct OuterNotFound(ResponseFn);
+
+impl ErrorResponses {
+    fn not_found(&self, req: &Request<Body>) -> Response<Body> {
+        match (&self.not_found, req.extensions().get::<OuterNotFound>()) {
+            (Some(res), _) | (None, Some(OuterNotFound(res))) => res(),
+            (None, None) => self.status(StatusCode::NOT_FOUND),
+        }
+    }
+
+    fn method_not_allowed(&self) -> Response<Body> {
+        match &self.method_not_allowed {
+            Some(res) => res(),
+            None => self.status(StatusCode::METHOD_NOT_ALLOWED),
+        }
+    }
+
+    // Errors of fallible services are not propagated, as touche would drop the connection without
+    // responding.
+    fn service_error(&self, err: Box<dyn Error + Send + Sync>) -> Response<Body> {
+        match &self.internal_server_error {
+            Some(res) => res(),
+            #[cfg(feature = "json")]
+            None if self.problem_details => {
+                let problem = Problem::new(StatusCode::INTERNAL_SERVER_ERROR);
+                if self.error_details {
+                    problem.with_detail(err.to_string()).into_response()
+                } else {
+                    problem.into_response()
                 }
             }
-            Err(_) => Ok(StatusCode::NOT_FOUND.into_response()),
+            None => err.into_response(),
+        }
+    }
+
+    // The built-in response for `status`, without a body unless problem details are enabled.
+    fn status(&self, status: StatusCode) -> Response<Body> {
+        #[cfg(feature = "json")]
+        if self.problem_details {
+            return Problem::new(status).into_response();
+        }
+        status.into_response()
+    }
+}
+
+fn has_body(req: &Request<Body>) -> bool {
+    let headers = req.headers();
+    headers.contains_key(header::TRANSFER_ENCODING)
+        || headers
+            .get(header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok()?.trim().parse::<u64>().ok())
+            .is_some_and(|len| len > 0)
+}
+
+// Ordered by matching precedence, static segments first.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Segment<'a> {
+    Static(&'a str),
+    Param,
+    CatchAll,
+}
+
+impl<'a> From<&'a str> for Segment<'a> {
+    fn from(segment: &'a str) -> Self {
+        if segment.starts_with(':') {
+            Segment::Param
+        } else if segment.starts_with('*') {
+            Segment::CatchAll
+        } else {
+            Segment::Static(segment)
+        }
+    }
+}
+
+fn segments_overlap(a: &[Segment], b: &[Segment]) -> bool {
+    match (a, b) {
+        ([], []) => true,
+        ([Segment::CatchAll, ..], [_, ..]) | ([_, ..], [Segment::CatchAll, ..]) => true,
+        ([], _) | (_, []) => false,
+        ([Segment::Static(a_seg), a_rest @ ..], [Segment::Static(b_seg), b_rest @ ..]) => {
+            a_seg == b_seg && segments_overlap(a_rest, b_rest)
         }
+        ([_, a_rest @ ..], [_, b_rest @ ..]) => segments_overlap(a_rest, b_rest),
     }
 }
@@ -0,0 +1,128 @@
+//! [`Service`]s serving files from the file system.
+
+use std::{
+    convert::Infallible,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use percent_encoding::percent_decode_str;
+use touche::{header, server::Service, Body, Method, Request, Response, StatusCode};
+
+use crate::{response::IntoResponse, HttpDate};
+
+/// Serves the files under a directory, mapping the request path to a file path, e.g.
+/// `/css/app.css` to `public/css/app.css`.
+///
+/// The path is percent-decoded, and paths escaping the directory, with `..` segments or
+/// backslashes, get a `404 Not Found`, as do directories. Absolute paths are taken as relative to
+/// the directory. Symbolic links under the directory are followed, so they must not point to files
+/// that shouldn't be served. Files are served like [`ServeFile`] does, answering conditional
+/// requests.
+///
+/// ```no_run
+/// # use spike::{routing::get, services::ServeDir, Router};
+/// let router = Router::new()
+///     .route("/", get(|| "home"))
+///     .nest_service("/static", ServeDir::new("public"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServeDir {
+    root: PathBuf,
+}
+
+impl ServeDir {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+impl Service for ServeDir {
+    type Body = Body;
+    type Error = Infallible;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        match resolve(&self.root, req.uri().path()) {
+            Some(path) => Ok(serve(path, &req)),
+            None => Ok(StatusCode::NOT_FOUND.into_response()),
+        }
+    }
+}
+
+/// Serves a single file for every request, like a `favicon.ico`.
+///
+/// Responses carry the modification time of the file as `Last-Modified`, and requests with an
+/// `If-Modified-Since` at or after it get a `304 Not Modified` without reading the file. Only
+/// `GET` and `HEAD` are allowed.
+///
+/// ```no_run
+/// # use spike::{services::ServeFile, Router};
+/// let router = Router::new().route_service("/favicon.ico", ServeFile::new("assets/favicon.ico"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ServeFile {
+    path: PathBuf,
+}
+
+impl ServeFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl Service for ServeFile {
+    type Body = Body;
+    type Error = Infallible;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        Ok(serve(self.path.clone(), &req))
+    }
+}
+
+fn serve(path: PathBuf, req: &Request<Body>) -> Response<Body> {
+    if !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return Response::builder()
+            .status(StatusCode::METHOD_NOT_ALLOWED)
+            .header(header::ALLOW, "GET, HEAD")
+            .body(Body::empty())
+            .unwrap();
+    }
+
+    // Files whose modification time can't be read, or can't be sent, are always served in full.
+    let modified = fs::metadata(&path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|time| HttpDate::try_from(time).ok());
+
+    let since = req
+        .headers()
+        .get(header::IF_MODIFIED_SINCE)
+        .and_then(|since| since.to_str().ok())
+        .and_then(HttpDate::parse);
+
+    match (modified, since) {
+        (Some(modified), Some(since)) if modified <= since => Response::builder()
+            .status(StatusCode::NOT_MODIFIED)
+            .header(header::LAST_MODIFIED, modified)
+            .body(Body::empty())
+            .unwrap(),
+        _ => path.into_response(),
+    }
+}
+
+// Pushes every segment of the decoded path onto the root, as long as the platform reads it as a
+// single file name, so not as `..`, a root or a drive like `C:` on Windows.
+fn resolve(root: &Path, path: &str) -> Option<PathBuf> {
+    let path = percent_decode_str(path).decode_utf8().ok()?;
+    let mut resolved = root.to_path_buf();
+    for segment in path.split('/').filter(|s| !s.is_empty() && *s != ".") {
+        let mut components = Path::new(segment).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(name)), None) if !segment.contains(['\\', '\0']) => {
+                resolved.push(name)
+            }
+            _ => return None,
+        }
+    }
+    Some(resolved)
+}
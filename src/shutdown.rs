@@ -0,0 +1,210 @@
+use std::{
+    collections::HashMap,
+    io, iter,
+    net::{Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use touche::{
+    header,
+    http::HeaderValue,
+    server::{MakeService, ServerBuilder, Service},
+    Body, Connection, Request, Response, StatusCode,
+};
+
+// Connections are tracked by their peer address, which is unique among the open ones.
+#[derive(Default)]
+struct State {
+    shutting_down: bool,
+    connections: HashMap<SocketAddr, Tracked>,
+}
+
+struct Tracked {
+    stream: TcpStream,
+    busy: bool,
+}
+
+#[derive(Default)]
+struct GracefulShutdown {
+    state: Mutex<State>,
+    closed: Condvar,
+}
+
+impl GracefulShutdown {
+    fn is_shutting_down(&self) -> bool {
+        self.state.lock().unwrap().shutting_down
+    }
+
+    // A connection accepted right before the shutdown began may be tracked after `begin` went
+    // through the idle ones, so it's checked again here, under the same lock.
+    fn track(&self, addr: SocketAddr, stream: &TcpStream) {
+        if let Ok(stream) = stream.try_clone() {
+            let mut state = self.state.lock().unwrap();
+            if state.shutting_down {
+                stream.shutdown(Shutdown::Read).ok();
+            }
+            let tracked = Tracked {
+                stream,
+                busy: false,
+            };
+            state.connections.insert(addr, tracked);
+        }
+    }
+
+    // Returns whether the server is shutting down.
+    fn set_busy(&self, addr: SocketAddr, busy: bool) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if let Some(tracked) = state.connections.get_mut(&addr) {
+            tracked.busy = busy;
+        }
+        state.shutting_down
+    }
+
+    fn untrack(&self, addr: SocketAddr) {
+        self.state.lock().unwrap().connections.remove(&addr);
+        self.closed.notify_all();
+    }
+
+    // Idle keep-alive connections are waiting for their next request, so stop reading from them,
+    // which makes touche close them. Busy ones are closed after their current response.
+    fn begin(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.shutting_down = true;
+        for tracked in state.connections.values().filter(|tracked| !tracked.busy) {
+            tracked.stream.shutdown(Shutdown::Read).ok();
+        }
+    }
+
+    // Waits for the open connections to be closed, force closing the ones left after `grace`.
+    fn drain(&self, grace: Duration) {
+        let deadline = Instant::now() + grace;
+        let mut state = self.state.lock().unwrap();
+        while !state.connections.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = self.closed.wait_timeout(state, deadline - now).unwrap().0;
+        }
+        for tracked in state.connections.values() {
+            tracked.stream.shutdown(Shutdown::Both).ok();
+        }
+    }
+}
+
+// Serves the connections accepted by `listener` until `shutdown` returns, then stops accepting
+// new ones and waits up to `grace` for the open ones to finish.
+//
+// `shutdown` runs on its own thread. When it returns, the accept loop of touche's `Server` is woken
+// by a connection to the listener itself, and ends, while the connections being served keep
+// running on their threads.
+pub(crate) fn serve<S, F>(
+    listener: TcpListener,
+    builder: ServerBuilder,
+    service: S,
+    shutdown: F,
+    grace: Duration,
+) -> io::Result<()>
+where
+    S: Service + Send + Clone + 'static,
+    F: FnOnce() + Send + 'static,
+{
+    let graceful = Arc::new(GracefulShutdown::default());
+
+    let mut wake_addr = listener.local_addr()?;
+    match wake_addr {
+        SocketAddr::V4(_) if wake_addr.ip().is_unspecified() => {
+            wake_addr.set_ip(Ipv4Addr::LOCALHOST.into())
+        }
+        SocketAddr::V6(_) if wake_addr.ip().is_unspecified() => {
+            wake_addr.set_ip(Ipv6Addr::LOCALHOST.into())
+        }
+        _ => {}
+    }
+
+    thread::spawn({
+        let graceful = graceful.clone();
+        move || {
+            shutdown();
+            graceful.begin();
+            TcpStream::connect(wake_addr).ok();
+        }
+    });
+
+    let connections = iter::from_fn({
+        let graceful = graceful.clone();
+        move || {
+            let (stream, addr) = listener.accept().ok()?;
+            if graceful.is_shutting_down() {
+                return None;
+            }
+            graceful.track(addr, &stream);
+            Some(Connection::from((stream, addr)))
+        }
+    });
+
+    builder
+        .from_connections(connections)
+        .make_service(MakeGracefulService {
+            svc: service,
+            graceful: graceful.clone(),
+        })?;
+
+    graceful.drain(grace);
+
+    Ok(())
+}
+
+#[derive(Clone)]
+struct MakeGracefulService<S> {
+    svc: S,
+    graceful: Arc<GracefulShutdown>,
+}
+
+impl<S: Service + Clone> MakeService for MakeGracefulService<S> {
+    type Service = GracefulService<S>;
+    type Error = io::Error;
+
+    fn call(&self, conn: &Connection) -> Result<Self::Service, Self::Error> {
+        let addr = conn.peer_addr().ok_or(io::ErrorKind::NotConnected)?;
+        Ok(GracefulService {
+            svc: self.svc.clone(),
+            graceful: self.graceful.clone(),
+            addr,
+        })
+    }
+}
+
+// Serves a single connection, which is untracked when touche drops the service after closing it.
+struct GracefulService<S> {
+    svc: S,
+    graceful: Arc<GracefulShutdown>,
+    addr: SocketAddr,
+}
+
+impl<S: Service> Service for GracefulService<S> {
+    type Body = S::Body;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Self::Body>, Self::Error> {
+        self.graceful.set_busy(self.addr, true);
+        let mut res = self.svc.call(req)?;
+        if self.graceful.set_busy(self.addr, false) {
+            res.headers_mut()
+                .insert(header::CONNECTION, HeaderValue::from_static("close"));
+        }
+        Ok(res)
+    }
+
+    fn should_continue(&self, req: &Request<Body>) -> StatusCode {
+        self.svc.should_continue(req)
+    }
+}
+
+impl<S> Drop for GracefulService<S> {
+    fn drop(&mut self) {
+        self.graceful.untrack(self.addr);
+    }
+}
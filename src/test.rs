@@ -0,0 +1,102 @@
+//! Helpers for testing handlers and routers without running a server.
+
+use touche::{
+    http::{request, HeaderName, HeaderValue, Method},
+    server::Service,
+    Body, Request, Response,
+};
+
+/// Builds a request and calls a service, like a [`Router`](crate::Router), with it.
+///
+/// Extensions can be inserted directly, so handlers reading an
+/// [`Extension`](crate::extract::Extension) are tested without running the middleware that
+/// inserts it on the real application.
+///
+/// ```no_run
+/// # use spike::{extract::Extension, http::Method, routing::get, test::TestRequestBuilder, Router};
+/// # #[derive(Clone)]
+/// # struct User { id: u64 }
+/// # fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// # let router = Router::new().route("/me", get(|Extension(user): Extension<User>| user.id.to_string()));
+/// let res = TestRequestBuilder::new(Method::GET, "/me")
+///     .extension(User { id: 1 })
+///     .call(&router)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TestRequestBuilder {
+    builder: request::Builder,
+    body: Body,
+}
+
+impl TestRequestBuilder {
+    pub fn new(method: Method, uri: &str) -> Self {
+        TestRequestBuilder {
+            builder: Request::builder().method(method).uri(uri),
+            body: Body::empty(),
+        }
+    }
+
+    pub fn get(uri: &str) -> Self {
+        Self::new(Method::GET, uri)
+    }
+
+    pub fn post(uri: &str) -> Self {
+        Self::new(Method::POST, uri)
+    }
+
+    pub fn put(uri: &str) -> Self {
+        Self::new(Method::PUT, uri)
+    }
+
+    pub fn patch(uri: &str) -> Self {
+        Self::new(Method::PATCH, uri)
+    }
+
+    pub fn delete(uri: &str) -> Self {
+        Self::new(Method::DELETE, uri)
+    }
+
+    pub fn header<K, V>(self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<touche::http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<touche::http::Error>,
+    {
+        TestRequestBuilder {
+            builder: self.builder.header(name, value),
+            ..self
+        }
+    }
+
+    pub fn body(self, body: impl Into<Body>) -> Self {
+        TestRequestBuilder {
+            body: body.into(),
+            ..self
+        }
+    }
+
+    pub fn extension<T: Clone + Send + Sync + 'static>(self, extension: T) -> Self {
+        TestRequestBuilder {
+            builder: self.builder.extension(extension),
+            ..self
+        }
+    }
+
+    /// Builds the request and calls the `service` with it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the method, URI or any of the headers is invalid.
+    pub fn call<S>(self, service: &S) -> Result<Response<S::Body>, S::Error>
+    where
+        S: Service,
+    {
+        let req = self
+            .builder
+            .body(self.body)
+            .unwrap_or_else(|err| panic!("invalid test request: {err}"));
+        service.call(req)
+    }
+}
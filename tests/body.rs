@@ -0,0 +1,59 @@
+use std::{
+    io::Read,
+    sync::{atomic::Ordering, Arc},
+};
+
+use spike::{
+    body::CountingBody,
+    http::{HeaderMap, HeaderValue},
+    Body,
+};
+use touche::{body::Chunk, HttpBody};
+
+#[test]
+fn counts_the_bytes_read() {
+    let body = CountingBody::new(Body::from("hello, world"));
+    let counter = body.counter();
+    assert_eq!(body.len(), Some(12));
+    assert_eq!(counter.load(Ordering::Relaxed), 0);
+
+    let mut reader = body.into_reader();
+    let mut buf = [0; 5];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(counter.load(Ordering::Relaxed), 5);
+
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(counter.load(Ordering::Relaxed), 12);
+}
+
+#[test]
+fn counts_the_data_of_the_chunks_consumed() {
+    let mut trailers = HeaderMap::new();
+    trailers.insert("x-checksum", HeaderValue::from_static("abc"));
+    let body = CountingBody::new(Body::from_iter([
+        Chunk::Data(b"hello".to_vec()),
+        Chunk::Data(b", world".to_vec()),
+        Chunk::Trailers(trailers),
+    ]));
+    let counter = body.counter();
+    assert_eq!(body.len(), None);
+
+    let mut chunks = body.into_chunks();
+    chunks.next().unwrap().unwrap();
+    assert_eq!(counter.load(Ordering::Relaxed), 5);
+
+    assert_eq!(chunks.count(), 2);
+    assert_eq!(counter.load(Ordering::Relaxed), 12);
+}
+
+#[test]
+fn shared_counter_accumulates_every_body() {
+    let counter = Arc::default();
+    for body in ["hello", ", world"] {
+        CountingBody::with_counter(Body::from(body), Arc::clone(&counter))
+            .into_bytes()
+            .unwrap();
+    }
+    assert_eq!(counter.load(Ordering::Relaxed), 12);
+}
@@ -0,0 +1,23 @@
+//! Helpers shared by the integration tests, each of which uses only some of them.
+#![allow(dead_code)]
+
+use spike::{
+    http::{header, Response},
+    test::TestRequestBuilder,
+    Body, Router,
+};
+use touche::HttpBody;
+
+/// Reads the whole body of `res` as text.
+pub fn text(res: Response<Body>) -> String {
+    String::from_utf8(res.into_body().into_bytes().unwrap()).unwrap()
+}
+
+/// Posts the `json` body to the `path` of the `router`.
+pub fn post_json(router: &Router, path: &str, json: &str) -> Response<Body> {
+    TestRequestBuilder::post(path)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(json.to_owned())
+        .call(router)
+        .unwrap()
+}
@@ -0,0 +1,100 @@
+use spike::{
+    http::{header, Method, Response, StatusCode},
+    layer::Cors,
+    routing::get,
+    test::TestRequestBuilder,
+    Body, Router,
+};
+
+fn router(cors: Cors) -> Router {
+    Router::new().route("/users", get(|| "users")).before(cors)
+}
+
+fn preflight(router: &Router, origin: &str) -> Response<Body> {
+    TestRequestBuilder::new(Method::OPTIONS, "/users")
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, "GET")
+        .call(router)
+        .unwrap()
+}
+
+#[test]
+fn preflight_from_an_allowed_origin() {
+    let router = router(
+        Cors::new()
+            .allow_origins(["https://example.com"])
+            .allow_methods([Method::GET, Method::POST])
+            .allow_headers([header::CONTENT_TYPE]),
+    );
+
+    let res = preflight(&router, "https://EXAMPLE.com");
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    let headers = res.headers();
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://EXAMPLE.com"
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_METHODS], "GET, POST");
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_HEADERS],
+        "content-type"
+    );
+}
+
+#[test]
+fn other_origins_get_no_cors_headers() {
+    let router = router(Cors::new().allow_origins(["https://example.com"]));
+
+    let res = preflight(&router, "https://evil.com");
+    assert!(!res
+        .headers()
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+
+    let res = TestRequestBuilder::get("/users")
+        .header(header::ORIGIN, "https://evil.com")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!res
+        .headers()
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN));
+    assert_eq!(res.headers()[header::VARY], "origin");
+}
+
+#[test]
+fn any_origin_gets_the_wildcard() {
+    let router = router(Cors::new().allow_any_origin());
+
+    let res = TestRequestBuilder::get("/users")
+        .header(header::ORIGIN, "https://example.com")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+    assert!(!res.headers().contains_key(header::VARY));
+}
+
+#[test]
+fn credentials_send_back_the_listed_origin() {
+    let router = router(
+        Cors::new()
+            .allow_origins(["https://example.com"])
+            .allow_credentials(true),
+    );
+
+    let res = TestRequestBuilder::get("/users")
+        .header(header::ORIGIN, "https://example.com")
+        .call(&router)
+        .unwrap();
+    let headers = res.headers();
+    assert_eq!(
+        headers[header::ACCESS_CONTROL_ALLOW_ORIGIN],
+        "https://example.com"
+    );
+    assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+}
+
+#[test]
+#[should_panic(expected = "CORS credentials can't be allowed for any origin")]
+fn credentials_for_any_origin_panic() {
+    router(Cors::new().allow_any_origin().allow_credentials(true));
+}
@@ -0,0 +1,116 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use spike::{
+    http::{header, StatusCode},
+    response::{IntoResponse, RetryAfter},
+    DateOutOfRange, HttpDate,
+};
+
+// Sun, 06 Nov 1994 08:49:37 GMT
+const NOV_1994: u64 = 784_111_777;
+
+fn nov_1994() -> HttpDate {
+    HttpDate::try_from(UNIX_EPOCH + Duration::from_secs(NOV_1994)).unwrap()
+}
+
+#[test]
+fn parses_imf_fixdate() {
+    assert_eq!(
+        HttpDate::parse("Sun, 06 Nov 1994 08:49:37 GMT"),
+        Some(nov_1994())
+    );
+}
+
+#[test]
+fn parses_rfc_850() {
+    assert_eq!(
+        HttpDate::parse("Sunday, 06-Nov-94 08:49:37 GMT"),
+        Some(nov_1994())
+    );
+}
+
+#[test]
+fn parses_asctime() {
+    assert_eq!(
+        HttpDate::parse("Sun Nov  6 08:49:37 1994"),
+        Some(nov_1994())
+    );
+}
+
+#[test]
+fn rejects_invalid_dates() {
+    assert_eq!(HttpDate::parse(""), None);
+    assert_eq!(HttpDate::parse("yesterday"), None);
+    assert_eq!(HttpDate::parse("Sun, 32 Nov 1994 08:49:37 GMT"), None);
+}
+
+#[test]
+fn formats_as_imf_fixdate() {
+    assert_eq!(nov_1994().format(), "Sun, 06 Nov 1994 08:49:37 GMT");
+    assert_eq!(nov_1994().to_string(), "Sun, 06 Nov 1994 08:49:37 GMT");
+}
+
+#[test]
+fn round_trips_through_system_time() {
+    let time = UNIX_EPOCH + Duration::from_secs(NOV_1994);
+    assert_eq!(SystemTime::from(HttpDate::try_from(time).unwrap()), time);
+
+    let date = nov_1994();
+    assert_eq!(HttpDate::parse(&date.format()), Some(date));
+}
+
+#[test]
+fn truncates_to_seconds() {
+    let time = UNIX_EPOCH + Duration::from_millis(NOV_1994 * 1000 + 999);
+    assert_eq!(HttpDate::try_from(time), Ok(nov_1994()));
+}
+
+#[test]
+fn rejects_times_out_of_range() {
+    assert_eq!(
+        HttpDate::try_from(UNIX_EPOCH - Duration::from_secs(1)),
+        Err(DateOutOfRange)
+    );
+    assert_eq!(
+        HttpDate::try_from(UNIX_EPOCH + Duration::from_secs(253_402_300_800)),
+        Err(DateOutOfRange)
+    );
+    assert_eq!(
+        HttpDate::try_from(UNIX_EPOCH + Duration::from_secs(253_402_300_799))
+            .unwrap()
+            .format(),
+        "Fri, 31 Dec 9999 23:59:59 GMT"
+    );
+}
+
+#[test]
+fn retry_after_a_date() {
+    let time = UNIX_EPOCH + Duration::from_secs(NOV_1994);
+    let res = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        RetryAfter::at(time).unwrap(),
+        "busy",
+    )
+        .into_response();
+    assert_eq!(
+        res.headers()[header::RETRY_AFTER],
+        "Sun, 06 Nov 1994 08:49:37 GMT"
+    );
+
+    assert_eq!(
+        RetryAfter::at(UNIX_EPOCH - Duration::from_secs(1)),
+        Err(DateOutOfRange)
+    );
+}
+
+#[test]
+fn retry_after_a_delay() {
+    let res = (
+        StatusCode::SERVICE_UNAVAILABLE,
+        RetryAfter::after(Duration::from_millis(120_900)),
+        "busy",
+    )
+        .into_response();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.headers()[header::RETRY_AFTER], "120");
+}
@@ -0,0 +1,298 @@
+#![cfg(feature = "decompression")]
+
+use std::io::{Read, Write};
+
+use flate2::{
+    write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+    Compression,
+};
+
+use spike::{
+    extract::BodyStream,
+    http::{header, Response, StatusCode},
+    layer::RequestDecompression,
+    routing::post,
+    test::TestRequestBuilder,
+    Body, Router,
+};
+use touche::HttpBody;
+
+mod common;
+
+use common::text;
+
+// gzip of `hello world`.
+const GZIP: &str = "1f8b0800000000000203cb48cdc9c95728cf2fca49010085114a0d0b000000";
+// zlib of the alphabet four times forwards and four times backwards, on a dynamic Huffman block.
+const ZLIB_DYNAMIC: &str = "78daadc9b70100200800b05bb16345ec5eef13660d08a9b4b10e7d882917aadcfa\
+                            986b9f0b5fe79ebde6e88d2b959c62f0e8acd14a0af83b0f542858f9";
+// zlib of `stored`, on a stored block.
+const ZLIB_STORED: &str = "7801010600f9ff73746f726564093c0292";
+// zlib of 200000 zeros.
+const ZLIB_ZEROS: &str = concat!(
+    "78daedc13101000000c2a0f54f6d067fa0",
+    "000000000000000000000000000000000000000000000000000000000000000000000000",
+    "000000000000000000000000000000000000000000000000000000000000000000000000",
+    "000000000000000000000000000000000000000000000000000000000000000000000000",
+    "000000000000000000000000000000000000000000000000000000000000000000000000",
+    "000000000000000000000000000000000000000000000000000000000000000000000000",
+    "0000000000000000000000000080d7000d6d0001",
+);
+
+fn hex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn router(decompression: RequestDecompression) -> Router {
+    Router::new()
+        .route(
+            "/",
+            post(|mut body: BodyStream| {
+                let mut bytes = Vec::new();
+                body.read_to_end(&mut bytes).unwrap();
+                bytes
+            }),
+        )
+        .before(decompression)
+}
+
+fn upload(router: &Router, coding: &str, body: Vec<u8>) -> Response<Body> {
+    TestRequestBuilder::post("/")
+        .header(header::CONTENT_ENCODING, coding)
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(body)
+        .call(router)
+        .unwrap()
+}
+
+#[test]
+fn decodes_gzip() {
+    let res = upload(&router(RequestDecompression::new()), "gzip", hex(GZIP));
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(text(res), "hello world");
+}
+
+#[test]
+fn decodes_deflate_blocks() {
+    let router = router(RequestDecompression::new());
+
+    let alphabet = "abcdefghijklmnopqrstuvwxyz";
+    let backwards: String = alphabet.chars().rev().collect();
+    let res = upload(&router, "deflate", hex(ZLIB_DYNAMIC));
+    assert_eq!(text(res), alphabet.repeat(4) + &backwards.repeat(4));
+
+    let res = upload(&router, "deflate", hex(ZLIB_STORED));
+    assert_eq!(text(res), "stored");
+}
+
+#[test]
+fn rejects_corrupt_bodies() {
+    let router = router(RequestDecompression::new());
+
+    let mut checksum = hex(GZIP);
+    let crc = checksum.len() - 8;
+    checksum[crc] ^= 0xff;
+    let res = upload(&router, "gzip", checksum);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let truncated = hex(ZLIB_DYNAMIC)[..30].to_vec();
+    let res = upload(&router, "deflate", truncated);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = upload(&router, "gzip", b"not gzip at all".to_vec());
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn rejects_bodies_decompressing_past_the_limit() {
+    let res = upload(
+        &router(RequestDecompression::new().limit(64 * 1024)),
+        "deflate",
+        hex(ZLIB_ZEROS),
+    );
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+    let res = upload(
+        &router(RequestDecompression::new().limit(200_000)),
+        "deflate",
+        hex(ZLIB_ZEROS),
+    );
+    let body = res.into_body().into_bytes().unwrap();
+    assert!(body.len() == 200_000 && body.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn rejects_compressed_bodies_past_the_limit_without_decoding() {
+    let res = upload(
+        &router(RequestDecompression::new().limit(1024)),
+        "gzip",
+        vec![0; 4096],
+    );
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(res.headers()[header::CONNECTION], "close");
+}
+
+#[test]
+fn rejects_unsupported_codings() {
+    let res = upload(&router(RequestDecompression::new()), "br", hex(GZIP));
+    assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    assert_eq!(res.headers()[header::ACCEPT_ENCODING], "gzip, deflate");
+}
+
+fn gzip(data: &[u8], level: Compression) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), level);
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn zlib(data: &[u8], level: Compression) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), level);
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn raw_deflate(data: &[u8], level: Compression) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), level);
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+// Text repetitive enough for the encoder to pick dynamic Huffman blocks on the higher levels,
+// while `Compression::none()` writes stored blocks, and short inputs get fixed Huffman blocks.
+fn sample() -> Vec<u8> {
+    (0..2000)
+        .map(|i| format!("line {} of the sample, {}\n", i, i * 7 % 13))
+        .collect::<String>()
+        .into_bytes()
+}
+
+#[test]
+fn round_trips_every_coding_and_level() {
+    let router = router(RequestDecompression::new());
+    let samples = [sample(), b"hi".to_vec()];
+    let levels = [
+        Compression::none(),
+        Compression::fast(),
+        Compression::best(),
+    ];
+
+    for data in &samples {
+        for level in levels {
+            for (coding, encoded) in [
+                ("gzip", gzip(data, level)),
+                ("deflate", zlib(data, level)),
+                ("deflate", raw_deflate(data, level)),
+            ] {
+                let res = upload(&router, coding, encoded);
+                assert_eq!(res.status(), StatusCode::OK, "{coding} {level:?}");
+                assert_eq!(&res.into_body().into_bytes().unwrap(), data);
+            }
+        }
+    }
+}
+
+#[test]
+fn decodes_concatenated_gzip_members_and_stacked_codings() {
+    let router = router(RequestDecompression::new());
+
+    let mut members = gzip(b"hello ", Compression::fast());
+    members.extend(gzip(b"world", Compression::best()));
+    let res = upload(&router, "gzip", members);
+    assert_eq!(text(res), "hello world");
+
+    let stacked = gzip(&zlib(b"stacked", Compression::fast()), Compression::fast());
+    let res = upload(&router, "deflate, gzip", stacked);
+    assert_eq!(text(res), "stacked");
+}
+
+#[test]
+fn rejects_truncated_bodies() {
+    let router = router(RequestDecompression::new());
+    let data = sample();
+
+    for (coding, encoded) in [
+        ("gzip", gzip(&data, Compression::best())),
+        ("deflate", zlib(&data, Compression::best())),
+        ("deflate", raw_deflate(&data, Compression::best())),
+        ("gzip", gzip(&data, Compression::none())),
+    ] {
+        for cut in [1, 4, encoded.len() / 2, encoded.len() - 1] {
+            let res = upload(&router, coding, encoded[..cut].to_vec());
+            assert_eq!(
+                res.status(),
+                StatusCode::BAD_REQUEST,
+                "{coding} cut at {cut}"
+            );
+        }
+    }
+}
+
+#[test]
+fn rejects_trailing_garbage_and_bad_checksums() {
+    let router = router(RequestDecompression::new());
+
+    let mut trailing = zlib(b"hello", Compression::fast());
+    trailing.extend(b"garbage");
+    let res = upload(&router, "deflate", trailing);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let mut trailing = gzip(b"hello", Compression::fast());
+    trailing.extend(b"garbage");
+    let res = upload(&router, "gzip", trailing);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let mut adler = zlib(b"hello", Compression::fast());
+    let last = adler.len() - 1;
+    adler[last] ^= 0xff;
+    let res = upload(&router, "deflate", adler);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let mut size = gzip(b"hello", Compression::fast());
+    let last = size.len() - 1;
+    size[last] ^= 0xff;
+    let res = upload(&router, "gzip", size);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn rejects_random_bytes() {
+    let router = router(RequestDecompression::new());
+    let mut state = 0x2545_f491_u32;
+    let mut random = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as u8
+    };
+
+    for len in [1, 2, 3, 16, 512] {
+        let bytes: Vec<u8> = (0..len).map(|_| random()).collect();
+        for coding in ["gzip", "deflate"] {
+            let res = upload(&router, coding, bytes.clone());
+            assert_ne!(res.status(), StatusCode::OK, "{coding} {bytes:02x?}");
+        }
+    }
+}
+
+#[test]
+fn rejects_decompression_bombs() {
+    let limit = 1024 * 1024;
+    let router = router(RequestDecompression::new().limit(limit));
+    let zeros = vec![0; 16 * limit];
+
+    for (coding, bomb) in [
+        ("gzip", gzip(&zeros, Compression::best())),
+        ("deflate", zlib(&zeros, Compression::best())),
+    ] {
+        assert!(bomb.len() < limit / 16);
+        let res = upload(&router, coding, bomb);
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    let nested = gzip(&gzip(&zeros, Compression::best()), Compression::best());
+    let res = upload(&router, "gzip, gzip", nested);
+    assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
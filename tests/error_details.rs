@@ -0,0 +1,114 @@
+#![cfg(feature = "json")]
+
+use std::error::Error;
+
+use serde::Deserialize;
+use spike::{
+    extract::{Extension, Query},
+    http::{header, StatusCode},
+    routing::{get, post},
+    test::TestRequestBuilder,
+    Json, Router,
+};
+
+mod common;
+
+use common::{post_json, text};
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct User {
+    name: String,
+    email: String,
+    age: u8,
+}
+
+fn router(error_details: bool) -> Router {
+    Router::new()
+        .route("/users", post(|Json(user): Json<User>| user.name))
+        .route("/search", get(|Query(user): Query<User>| user.name))
+        .route(
+            "/fail",
+            get(|| -> Result<&'static str, Box<dyn Error + Send + Sync>> {
+                Err("database is down".into())
+            }),
+        )
+        .route("/extension", get(|Extension(name): Extension<String>| name))
+        .error_details(error_details)
+}
+
+#[test]
+fn json_missing_field_is_described() {
+    let res = post_json(
+        &router(true),
+        "/users",
+        "{\n  \"name\": \"Ada\",\n  \"age\": 36\n}",
+    );
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        text(res),
+        "invalid JSON body: missing field `email` at line 4 column 1"
+    );
+}
+
+#[test]
+fn json_type_mismatch_is_described_with_the_field_path() {
+    let res = post_json(
+        &router(true),
+        "/users",
+        r#"{"name": "Ada", "email": "ada@example.com", "age": "old"}"#,
+    );
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body = text(res);
+    assert!(
+        body.starts_with("invalid JSON body: age: invalid type: string \"old\""),
+        "{body}"
+    );
+    assert!(body.contains("line 1 column"), "{body}");
+}
+
+#[test]
+fn details_are_hidden_when_disabled() {
+    let router = router(false);
+
+    let res = post_json(&router, "/users", r#"{"name": "Ada"}"#);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(text(res), "invalid JSON body");
+
+    let res = TestRequestBuilder::get("/search?name=Ada&age=old")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "invalid query string");
+
+    let res = TestRequestBuilder::get("/fail").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(text(res), "");
+
+    let res = TestRequestBuilder::get("/extension").call(&router).unwrap();
+    assert_eq!(text(res), "missing request extension");
+}
+
+#[test]
+fn handler_errors_are_described_when_enabled() {
+    let router = router(true);
+
+    let res = TestRequestBuilder::get("/fail").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "text/plain;charset=utf-8"
+    );
+    assert_eq!(res.headers()[header::CONTENT_LENGTH], "16");
+    assert_eq!(text(res), "database is down");
+
+    let res = TestRequestBuilder::get("/extension").call(&router).unwrap();
+    assert_eq!(
+        text(res),
+        "missing request extension `alloc::string::String`"
+    );
+
+    let res = TestRequestBuilder::get("/search?name=Ada&age=old")
+        .call(&router)
+        .unwrap();
+    assert!(text(res).starts_with("invalid query string: "));
+}
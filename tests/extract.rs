@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use spike::{
+    extract::{AcceptEncoding, Encoding, FromRequestPart, LossyString, Query},
+    http::{header, Request, StatusCode},
+    routing::{get, post},
+    test::TestRequestBuilder,
+    Router,
+};
+
+mod common;
+
+use common::text;
+
+fn query_router() -> Router {
+    Router::new()
+        .route(
+            "/optional",
+            get(
+                |Query(query): Query<Option<HashMap<String, String>>>| match query {
+                    Some(query) => format!("some {}", query.len()),
+                    None => "none".to_owned(),
+                },
+            ),
+        )
+        .route(
+            "/map",
+            get(|Query(query): Query<HashMap<String, String>>| {
+                query.get("a").cloned().unwrap_or_default()
+            }),
+        )
+}
+
+#[test]
+fn absent_query_is_none() {
+    let res = TestRequestBuilder::get("/optional")
+        .call(&query_router())
+        .unwrap();
+    assert_eq!(text(res), "none");
+}
+
+#[test]
+fn empty_query_is_some() {
+    let res = TestRequestBuilder::get("/optional?")
+        .call(&query_router())
+        .unwrap();
+    assert_eq!(text(res), "some 0");
+}
+
+#[test]
+fn present_query_is_some() {
+    let res = TestRequestBuilder::get("/optional?a=1")
+        .call(&query_router())
+        .unwrap();
+    assert_eq!(text(res), "some 1");
+}
+
+#[test]
+fn dynamic_queries_deserialize_into_maps() {
+    let router = query_router();
+
+    let res = TestRequestBuilder::get("/map?a=1&b=2")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(text(res), "1");
+
+    let res = TestRequestBuilder::get("/map").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(text(res), "");
+}
+
+fn accept_encoding(values: &[&str]) -> AcceptEncoding {
+    let mut req = Request::builder();
+    for value in values {
+        req = req.header(header::ACCEPT_ENCODING, *value);
+    }
+    let (mut parts, _) = req.body(()).unwrap().into_parts();
+    AcceptEncoding::from_request_parts(&mut parts).unwrap()
+}
+
+#[test]
+fn accept_encoding_is_ordered_by_quality() {
+    let accept = accept_encoding(&["deflate;q=0.5, gzip;q=0.8", "br, identity;q=0.1"]);
+    assert_eq!(
+        accept.iter().collect::<Vec<_>>(),
+        [
+            Encoding::Br,
+            Encoding::Gzip,
+            Encoding::Deflate,
+            Encoding::Identity
+        ]
+    );
+    assert_eq!(
+        accept.preferred(&[Encoding::Gzip, Encoding::Deflate]),
+        Some(Encoding::Gzip)
+    );
+}
+
+#[test]
+fn accept_encoding_excludes_identity_with_zero_quality() {
+    let accept = accept_encoding(&["gzip, identity;q=0"]);
+    assert!(accept.accepts(Encoding::Gzip));
+    assert!(!accept.accepts(Encoding::Identity));
+    assert_eq!(accept.preferred(&[Encoding::Identity]), None);
+
+    let accept = accept_encoding(&["*;q=0"]);
+    assert!(!accept.accepts(Encoding::Identity));
+    assert!(!accept.accepts(Encoding::Gzip));
+}
+
+#[test]
+fn accept_encoding_wildcard_matches_unlisted_codings() {
+    let accept = accept_encoding(&["gzip;q=0.5, *;q=0.5"]);
+    assert!(accept.accepts(Encoding::Br));
+    assert_eq!(
+        accept.preferred(&[Encoding::Br, Encoding::Gzip]),
+        Some(Encoding::Gzip)
+    );
+}
+
+#[test]
+fn accept_encoding_without_the_header_is_identity_only() {
+    let accept = accept_encoding(&[]);
+    assert!(accept.accepts(Encoding::Identity));
+    assert!(!accept.accepts(Encoding::Gzip));
+    assert_eq!(accept.iter().count(), 0);
+}
+
+#[test]
+fn accept_encoding_ignores_invalid_qualities_and_unknown_codings() {
+    let accept = accept_encoding(&["gzip;q=2, br;q=0.1234, zstd, deflate;q=0.001"]);
+    assert_eq!(accept.iter().collect::<Vec<_>>(), [Encoding::Deflate]);
+    assert!(!accept.accepts(Encoding::Gzip));
+}
+
+fn text_router() -> Router {
+    Router::new()
+        .route("/lossy", post(|LossyString(body): LossyString| body))
+        .route("/strict", post(|body: String| body))
+}
+
+#[test]
+fn lossy_string_replaces_invalid_utf8() {
+    let res = TestRequestBuilder::post("/lossy")
+        .body(b"caf\xe9 \xff!".to_vec())
+        .call(&text_router())
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(text(res), "caf\u{FFFD} \u{FFFD}!");
+}
+
+#[test]
+fn lossy_string_keeps_valid_utf8() {
+    let res = TestRequestBuilder::post("/lossy")
+        .body("café")
+        .call(&text_router())
+        .unwrap();
+    assert_eq!(text(res), "café");
+}
+
+#[test]
+fn string_rejects_invalid_utf8() {
+    let res = TestRequestBuilder::post("/strict")
+        .body(b"caf\xe9".to_vec())
+        .call(&text_router())
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+fn pairs(pairs: Vec<(String, String)>) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{key}:{value}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn query_pairs_keep_repeated_keys_in_order() {
+    let router = Router::new().route(
+        "/pairs",
+        get(|Query(query): Query<Vec<(String, String)>>| pairs(query)),
+    );
+
+    let res = TestRequestBuilder::get("/pairs?a=1&b=x%20y&a=2")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "a:1 b:x y a:2");
+
+    let res = TestRequestBuilder::get("/pairs").call(&router).unwrap();
+    assert_eq!(text(res), "");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn form_pairs_keep_repeated_keys_in_order() {
+    use spike::extract::ContentTyped;
+
+    let router = Router::new().route(
+        "/pairs",
+        post(|ContentTyped(form): ContentTyped<Vec<(String, String)>>| pairs(form)),
+    );
+
+    let res = TestRequestBuilder::post("/pairs")
+        .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body("a=1&a=2")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "a:1 a:2");
+}
@@ -0,0 +1,68 @@
+use spike::{
+    http::{header, StatusCode},
+    layer::HostFilter,
+    routing::get,
+    test::TestRequestBuilder,
+    Router,
+};
+
+fn router() -> Router {
+    Router::new()
+        .route("/", get(|| "home"))
+        .before(HostFilter::new(["example.com", "*.api.example.com"]))
+}
+
+fn status(host: Option<&str>) -> StatusCode {
+    let req = TestRequestBuilder::get("/");
+    let req = match host {
+        Some(host) => req.header(header::HOST, host),
+        None => req,
+    };
+    req.call(&router()).unwrap().status()
+}
+
+#[test]
+fn allowed_hosts_are_routed() {
+    assert_eq!(status(Some("example.com")), StatusCode::OK);
+    assert_eq!(status(Some("Example.COM:8080")), StatusCode::OK);
+    assert_eq!(status(Some("v1.api.example.com")), StatusCode::OK);
+}
+
+#[test]
+fn disallowed_hosts_are_misdirected() {
+    for host in [
+        "evil.com",
+        "example.com.evil.com",
+        "www.example.com",
+        "api.example.com",
+        "evilapi.example.com",
+        "[::1]:8080",
+    ] {
+        assert_eq!(
+            status(Some(host)),
+            StatusCode::MISDIRECTED_REQUEST,
+            "{host}"
+        );
+    }
+}
+
+#[test]
+fn missing_host_is_a_bad_request() {
+    assert_eq!(status(None), StatusCode::BAD_REQUEST);
+    assert_eq!(status(Some("")), StatusCode::BAD_REQUEST);
+    assert_eq!(status(Some(":8080")), StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn absolute_request_targets_win_over_the_host_header() {
+    let res = TestRequestBuilder::get("http://evil.com/")
+        .header(header::HOST, "example.com")
+        .call(&router())
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::MISDIRECTED_REQUEST);
+
+    let res = TestRequestBuilder::get("http://example.com/")
+        .call(&router())
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
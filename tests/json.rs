@@ -0,0 +1,126 @@
+#![cfg(feature = "json")]
+
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+};
+
+use serde::{Deserialize, Serialize};
+use spike::{
+    extract::StrictJson,
+    http::{header, StatusCode},
+    response::{IntoResponse, PrettyJson},
+    routing::post,
+    test::TestRequestBuilder,
+    Body, Json, Router,
+};
+
+mod common;
+
+use common::{post_json, text};
+
+#[derive(Serialize)]
+struct User {
+    name: &'static str,
+    age: u8,
+}
+
+const USER: User = User {
+    name: "rust",
+    age: 8,
+};
+
+#[test]
+fn json_responses_are_compact() {
+    let res = Json(USER).into_response();
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+    assert_eq!(text(res), r#"{"name":"rust","age":8}"#);
+}
+
+#[test]
+fn pretty_json_responses_are_indented() {
+    let res = PrettyJson(USER).into_response();
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+    assert_eq!(text(res), "{\n  \"name\": \"rust\",\n  \"age\": 8\n}");
+}
+
+#[test]
+fn serialization_errors_ignore_the_status_of_the_tuple() {
+    // Maps with non-string keys can't be serialized as JSON objects.
+    let map = HashMap::from([((1, 2), "point")]);
+    let res = (StatusCode::CREATED, [(header::ETAG, "\"v1\"")], Json(map)).into_response();
+
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!res.headers().contains_key(header::ETAG));
+}
+
+#[derive(Deserialize)]
+struct NewUser {
+    name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StrictUser {
+    name: String,
+}
+
+fn strictness_router() -> Router {
+    Router::new()
+        .route("/lenient", post(|Json(user): Json<NewUser>| user.name))
+        .route(
+            "/strict",
+            post(|StrictJson(user): StrictJson<NewUser>| user.name),
+        )
+        .route(
+            "/strict-fields",
+            post(|StrictJson(user): StrictJson<StrictUser>| user.name),
+        )
+}
+
+#[test]
+fn strict_json_rejects_trailing_garbage() {
+    let router = strictness_router();
+    let res = post_json(&router, "/strict", r#"{"name":"rust"} {"name":"evil"}"#);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = post_json(&router, "/strict", "{\"name\":\"rust\"} \n");
+    assert_eq!(text(res), "rust");
+
+    let res = post_json(&router, "/lenient", r#"{"name":"rust"} garbage"#);
+    assert_eq!(text(res), "rust");
+}
+
+#[test]
+fn unknown_fields_are_rejected_by_the_type() {
+    let router = strictness_router();
+    let res = post_json(&router, "/strict", r#"{"name":"rust","admin":true}"#);
+    assert_eq!(text(res), "rust");
+
+    let res = post_json(&router, "/strict-fields", r#"{"name":"rust","admin":true}"#);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+struct BrokenReader;
+
+impl Read for BrokenReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::ConnectionReset.into())
+    }
+}
+
+#[test]
+fn body_read_errors_are_bad_requests() {
+    let router = strictness_router();
+    for path in ["/lenient", "/strict"] {
+        // Like a client disconnecting mid body.
+        let body = Body::from_reader(BrokenReader, None);
+        let res = TestRequestBuilder::post(path)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .call(&router)
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{path}");
+        assert_eq!(text(res), "error reading body");
+    }
+}
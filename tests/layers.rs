@@ -0,0 +1,221 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use spike::{
+    extract::MatchedPath,
+    http::{HeaderValue, Method, Request, Response, StatusCode},
+    layer::Layer,
+    routing::get,
+    test::TestRequestBuilder,
+    Body, Router,
+};
+use touche::server::Service;
+
+// Counts how many times it wraps a service.
+#[derive(Clone, Default)]
+struct CountLayers(Arc<AtomicUsize>);
+
+impl<S> Layer<S> for CountLayers {
+    type Service = S;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        inner
+    }
+}
+
+#[test]
+fn before_layers_are_built_once() {
+    let layers = CountLayers::default();
+    let router = Router::new()
+        .route("/", get(|| "home"))
+        .before(layers.clone());
+
+    for _ in 0..3 {
+        let res = TestRequestBuilder::get("/").call(&router).unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    assert_eq!(layers.0.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn before_layers_see_the_routes_registered_after_them() {
+    let router = Router::new()
+        .before(CountLayers::default())
+        .route("/late", get(|| "late"));
+
+    let res = TestRequestBuilder::get("/late").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+// Logs its name with the matched path of the request, and appends its name to the `x-layers`
+// header of the response.
+#[derive(Clone)]
+struct Tag {
+    name: &'static str,
+    log: Log,
+}
+
+impl<S> Layer<S> for Tag {
+    type Service = Tagged<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Tagged {
+            tag: self.clone(),
+            inner,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Tagged<S> {
+    tag: Tag,
+    inner: S,
+}
+
+impl<S: Service<Body = Body>> Service for Tagged<S> {
+    type Body = Body;
+    type Error = S::Error;
+
+    fn call(&self, req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        let matched = req
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or("-", MatchedPath::as_str);
+        let entry = format!("{} {matched}", self.tag.name);
+        self.tag.log.lock().unwrap().push(entry);
+
+        let mut res = self.inner.call(req)?;
+        res.headers_mut()
+            .append("x-layers", HeaderValue::from_static(self.tag.name));
+        Ok(res)
+    }
+}
+
+fn tag(name: &'static str, log: &Log) -> Tag {
+    Tag {
+        name,
+        log: log.clone(),
+    }
+}
+
+fn logged(log: &Log) -> Vec<String> {
+    log.lock().unwrap().clone()
+}
+
+#[test]
+fn route_layers_read_the_matched_path() {
+    let log = Log::default();
+    let router = Router::new()
+        .route("/users/:id", get(|| "user"))
+        .route_layer(tag("route", &log))
+        .before(tag("before", &log));
+
+    let res = TestRequestBuilder::get("/users/42").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(logged(&log), ["before -", "route /users/:id"]);
+}
+
+fn x_layers(res: &Response<Body>) -> Vec<&str> {
+    res.headers()
+        .get_all("x-layers")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect()
+}
+
+fn layered_router(log: &Log) -> Router {
+    Router::new()
+        .route("/users/:id", get(|| "user").layer(tag("method", log)))
+        .route_layer(tag("route1", log))
+        .route_layer(tag("route2", log))
+        .before(tag("before1", log))
+        .before(tag("before2", log))
+}
+
+#[test]
+fn layers_apply_from_the_last_added_on_matched_requests() {
+    let log = Log::default();
+    let router = layered_router(&log);
+
+    let res = TestRequestBuilder::get("/users/42").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        logged(&log),
+        [
+            "before2 -",
+            "before1 -",
+            "route2 /users/:id",
+            "route1 /users/:id",
+            "method /users/:id"
+        ]
+    );
+    assert_eq!(
+        x_layers(&res),
+        ["method", "route1", "route2", "before1", "before2"]
+    );
+}
+
+#[test]
+fn only_before_layers_apply_on_not_found_requests() {
+    let log = Log::default();
+    let router = layered_router(&log);
+
+    let res = TestRequestBuilder::get("/missing").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(logged(&log), ["before2 -", "before1 -"]);
+    assert_eq!(x_layers(&res), ["before1", "before2"]);
+}
+
+#[test]
+fn only_before_layers_apply_on_method_not_allowed_requests() {
+    let log = Log::default();
+    let router = layered_router(&log);
+
+    let res = TestRequestBuilder::delete("/users/42")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(logged(&log), ["before2 -", "before1 -"]);
+    assert_eq!(x_layers(&res), ["before1", "before2"]);
+}
+
+#[test]
+fn route_layers_skip_the_routes_registered_after_them() {
+    let log = Log::default();
+    let router = Router::new()
+        .route("/early", get(|| "early"))
+        .route_layer(tag("route", &log))
+        .route("/late", get(|| "late"))
+        .before(tag("before", &log));
+
+    let res = TestRequestBuilder::get("/late").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(x_layers(&res), ["before"]);
+
+    let res = TestRequestBuilder::get("/early").call(&router).unwrap();
+    assert_eq!(x_layers(&res), ["route", "before"]);
+}
+
+#[test]
+fn before_layers_wrap_the_requests_answered_by_the_router() {
+    let log = Log::default();
+    let router = Router::new()
+        .route("/users", get(|| "users"))
+        .redirect_trailing_slash(true)
+        .before(tag("before", &log));
+
+    let res = TestRequestBuilder::get("/users/").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(x_layers(&res), ["before"]);
+
+    let res = TestRequestBuilder::new(Method::OPTIONS, "/users")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert_eq!(x_layers(&res), ["before"]);
+}
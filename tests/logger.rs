@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex};
+
+use spike::{
+    http::{header, Response},
+    layer::{Layer, LogFormat, Logger, LoggerService},
+    routing::get,
+    test::TestRequestBuilder,
+    Body, Router,
+};
+use touche::HttpBody;
+
+fn logged(format: LogFormat) -> (LoggerService<Router>, Arc<Mutex<Vec<u8>>>) {
+    let router = Router::new().route("/users", get(|| "users")).route(
+        "/stream",
+        get(|| Response::new(Body::from_iter([&b"hello"[..], b" world"]))),
+    );
+    let sink = Arc::new(Mutex::new(Vec::new()));
+    let logger = Logger::new(sink.clone()).format(format);
+    (logger.layer(router), sink)
+}
+
+fn lines(sink: &Mutex<Vec<u8>>) -> Vec<String> {
+    let log = String::from_utf8(sink.lock().unwrap().clone()).unwrap();
+    log.lines().map(|line| line.to_owned()).collect()
+}
+
+// Drops the `- - [date]` part of the line, which changes on every run.
+fn without_date(line: &str) -> &str {
+    &line[line.find(']').unwrap() + 2..]
+}
+
+#[test]
+fn logs_the_combined_format_once_the_body_is_written() {
+    let (svc, sink) = logged(LogFormat::Combined);
+
+    let res = TestRequestBuilder::get("/users?page=2")
+        .header(header::USER_AGENT, "curl/8.0.1")
+        .call(&svc)
+        .unwrap();
+    assert!(lines(&sink).is_empty());
+
+    res.into_body().into_bytes().unwrap();
+    let lines = lines(&sink);
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("- - - ["));
+    assert_eq!(
+        without_date(&lines[0]),
+        r#""GET /users?page=2 HTTP/1.1" 200 5 "-" "curl/8.0.1""#
+    );
+}
+
+#[test]
+fn logs_the_common_format() {
+    let (svc, sink) = logged(LogFormat::Common);
+
+    let res = TestRequestBuilder::get("/missing").call(&svc).unwrap();
+    drop(res);
+    assert_eq!(
+        without_date(&lines(&sink)[0]),
+        r#""GET /missing HTTP/1.1" 404 -"#
+    );
+}
+
+#[test]
+fn logs_the_written_size_of_streaming_bodies() {
+    let (svc, sink) = logged(LogFormat::Common);
+
+    let res = TestRequestBuilder::get("/stream").call(&svc).unwrap();
+    assert_eq!(res.body().len(), None);
+    let chunks: Vec<_> = res.into_body().into_chunks().collect();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(
+        without_date(&lines(&sink)[0]),
+        r#""GET /stream HTTP/1.1" 200 11"#
+    );
+}
+
+#[test]
+fn logs_unwritten_bodies_without_size() {
+    let (svc, sink) = logged(LogFormat::Common);
+
+    let res = TestRequestBuilder::get("/stream").call(&svc).unwrap();
+    drop(res);
+    assert_eq!(
+        without_date(&lines(&sink)[0]),
+        r#""GET /stream HTTP/1.1" 200 -"#
+    );
+}
+
+#[test]
+fn escapes_the_request_line_and_headers() {
+    let (svc, sink) = logged(LogFormat::Combined);
+
+    let res = TestRequestBuilder::get("/users")
+        .header(header::USER_AGENT, "evil\" agent\\")
+        .call(&svc)
+        .unwrap();
+    drop(res);
+    assert!(lines(&sink)[0].ends_with(r#" "-" "evil\" agent\\""#));
+}
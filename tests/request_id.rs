@@ -0,0 +1,115 @@
+#![cfg(feature = "request-id")]
+
+use spike::{
+    extract::RequestId,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    layer::SetRequestId,
+    routing::get,
+    test::TestRequestBuilder,
+    Router,
+};
+
+mod common;
+
+use common::text;
+
+fn router(layer: SetRequestId) -> Router {
+    Router::new()
+        .route("/", get(|id: RequestId| id.to_string()))
+        .route(
+            "/header",
+            get(|headers: HeaderMap| format!("{:?}", headers.get("x-request-id"))),
+        )
+        .before(layer)
+}
+
+fn is_uuid_v4(id: &str) -> bool {
+    let groups: Vec<_> = id.split('-').map(str::len).collect();
+    groups == [8, 4, 4, 4, 12]
+        && id.chars().all(|c| c == '-' || c.is_ascii_hexdigit())
+        && id.as_bytes()[14] == b'4'
+        && matches!(id.as_bytes()[19], b'8' | b'9' | b'a' | b'b')
+}
+
+#[test]
+fn keeps_the_incoming_id() {
+    let res = TestRequestBuilder::get("/")
+        .header("x-request-id", "abc-123_x.y:z+/=")
+        .call(&router(SetRequestId::new()))
+        .unwrap();
+    assert_eq!(res.headers()["x-request-id"], "abc-123_x.y:z+/=");
+    assert_eq!(text(res), "abc-123_x.y:z+/=");
+}
+
+#[test]
+fn generates_a_uuid_v4_when_missing() {
+    let router = router(SetRequestId::new());
+    let first = text(TestRequestBuilder::get("/").call(&router).unwrap());
+    let second = text(TestRequestBuilder::get("/").call(&router).unwrap());
+    assert!(is_uuid_v4(&first), "{first}");
+    assert!(is_uuid_v4(&second), "{second}");
+    assert_ne!(first, second);
+
+    let res = TestRequestBuilder::get("/header").call(&router).unwrap();
+    let id = res.headers()["x-request-id"].to_str().unwrap().to_owned();
+    assert_eq!(text(res), format!("Some({id:?})"));
+}
+
+#[test]
+fn keeps_incoming_ids_up_to_the_max_length() {
+    let id = "a".repeat(SetRequestId::MAX_LEN);
+    let res = TestRequestBuilder::get("/")
+        .header("x-request-id", id.as_str())
+        .call(&router(SetRequestId::new()))
+        .unwrap();
+    assert_eq!(text(res), id);
+}
+
+#[test]
+fn replaces_invalid_incoming_ids() {
+    let router = router(SetRequestId::new());
+    let too_long = "a".repeat(SetRequestId::MAX_LEN + 1);
+    for id in [
+        HeaderValue::from_static(""),
+        HeaderValue::from_str(&too_long).unwrap(),
+        HeaderValue::from_static("with space"),
+        HeaderValue::from_static("\"quoted\""),
+        HeaderValue::from_static("tab\tid"),
+        HeaderValue::from_static("a;b"),
+        HeaderValue::from_bytes("ação".as_bytes()).unwrap(),
+    ] {
+        let res = TestRequestBuilder::get("/")
+            .header("x-request-id", id.clone())
+            .call(&router)
+            .unwrap();
+        let header = res.headers()["x-request-id"].clone();
+        let body = text(res);
+        assert!(is_uuid_v4(&body), "{id:?}: {body}");
+        assert_eq!(header, body.as_str());
+    }
+}
+
+#[test]
+fn generated_ids_that_are_not_header_values_are_only_kept_on_the_extractor() {
+    let router = router(SetRequestId::new().generator(|| "line\nbreak".to_owned()));
+
+    let res = TestRequestBuilder::get("/")
+        .header("x-request-id", "in valid")
+        .call(&router)
+        .unwrap();
+    assert!(!res.headers().contains_key("x-request-id"));
+    assert_eq!(text(res), "line\nbreak");
+
+    let res = TestRequestBuilder::get("/header")
+        .header("x-request-id", "in valid")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "None");
+}
+
+#[test]
+fn rejects_requests_without_the_layer() {
+    let router = Router::new().route("/", get(|id: RequestId| id.to_string()));
+    let res = TestRequestBuilder::get("/").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
@@ -0,0 +1,232 @@
+use std::{convert::Infallible, error::Error};
+
+use spike::{
+    extract::Path,
+    http::{header, HeaderValue, Method, StatusCode},
+    response::{Attachment, IntoResponse},
+    routing::get,
+    test::TestRequestBuilder,
+    Router,
+};
+
+mod common;
+
+use common::text;
+
+#[test]
+fn array_of_three_headers() {
+    let res = (
+        [
+            (header::CACHE_CONTROL, "no-cache"),
+            (header::ETAG, "\"v1\""),
+            (header::CONTENT_LANGUAGE, "en"),
+        ],
+        StatusCode::OK,
+    )
+        .into_response();
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "no-cache");
+    assert_eq!(res.headers()[header::ETAG], "\"v1\"");
+    assert_eq!(res.headers()[header::CONTENT_LANGUAGE], "en");
+}
+
+#[test]
+fn repeated_header_names_keep_every_value() {
+    let res = (
+        [
+            (header::SET_COOKIE, "a=1"),
+            (header::SET_COOKIE, "b=2"),
+            (header::SET_COOKIE, "c=3"),
+        ],
+        "ok",
+    )
+        .into_response();
+
+    let cookies: Vec<_> = res.headers().get_all(header::SET_COOKIE).iter().collect();
+    assert_eq!(cookies, ["a=1", "b=2", "c=3"]);
+}
+
+#[test]
+fn header_pairs_replace_the_response_headers() {
+    let res = (vec![(header::CONTENT_TYPE, "text/html")], "<p>hi</p>").into_response();
+
+    let types: Vec<_> = res.headers().get_all(header::CONTENT_TYPE).iter().collect();
+    assert_eq!(types, [HeaderValue::from_static("text/html")]);
+    assert_eq!(text(res), "<p>hi</p>");
+}
+
+#[test]
+fn invalid_header_value_is_an_internal_server_error() {
+    let res = ([(header::LOCATION, "/a\nb")], StatusCode::OK).into_response();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[test]
+fn empty_status_responses_have_a_zero_content_length() {
+    for status in [StatusCode::OK, StatusCode::NOT_FOUND, StatusCode::ACCEPTED] {
+        let res = status.into_response();
+        assert_eq!(res.headers()[header::CONTENT_LENGTH], "0", "{status}");
+    }
+}
+
+#[test]
+fn statuses_without_body_have_no_content_length() {
+    for status in [
+        StatusCode::CONTINUE,
+        StatusCode::SWITCHING_PROTOCOLS,
+        StatusCode::NO_CONTENT,
+        StatusCode::NOT_MODIFIED,
+    ] {
+        let res = status.into_response();
+        assert!(
+            !res.headers().contains_key(header::CONTENT_LENGTH),
+            "{status}"
+        );
+    }
+}
+
+#[test]
+fn automatic_options_have_no_content_length() {
+    let router = Router::new().route("/", get(|| "home"));
+
+    let res = TestRequestBuilder::new(Method::OPTIONS, "/")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert!(!res.headers().contains_key(header::CONTENT_LENGTH));
+}
+
+#[test]
+fn head_responses_match_the_get_ones() {
+    let router = Router::new()
+        .route("/text", get(|| "hello"))
+        .route("/empty", get(|| StatusCode::ACCEPTED))
+        .route("/no-content", get(|| StatusCode::NO_CONTENT));
+
+    for path in ["/text", "/empty", "/no-content"] {
+        let get = TestRequestBuilder::get(path).call(&router).unwrap();
+        let head = TestRequestBuilder::new(Method::HEAD, path)
+            .call(&router)
+            .unwrap();
+        assert_eq!(head.status(), get.status(), "{path}");
+        assert_eq!(head.headers(), get.headers(), "{path}");
+    }
+}
+
+#[test]
+fn status_as_the_last_element_is_an_empty_response() {
+    let res = ([(header::ETAG, "\"v1\"")], StatusCode::NO_CONTENT).into_response();
+
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert_eq!(res.headers()[header::ETAG], "\"v1\"");
+    assert_eq!(text(res), "");
+}
+
+#[test]
+fn leading_status_wins_over_the_last_one() {
+    let res = (StatusCode::CREATED, StatusCode::OK).into_response();
+    assert_eq!(res.status(), StatusCode::CREATED);
+}
+
+#[test]
+fn parts_apply_to_an_ok_result() {
+    let res = (StatusCode::CREATED, Ok::<_, StatusCode>("hi")).into_response();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(text(res), "hi");
+}
+
+#[test]
+fn err_result_overrides_the_parts() {
+    let res = (
+        StatusCode::CREATED,
+        [(header::ETAG, "\"v1\"")],
+        Err::<&str, _>((StatusCode::CONFLICT, "taken")),
+    )
+        .into_response();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    assert!(!res.headers().contains_key(header::ETAG));
+    assert_eq!(text(res), "taken");
+}
+
+#[test]
+fn attachment_with_an_ascii_filename() {
+    let res = (Attachment::new("report.csv"), "a,b").into_response();
+    assert_eq!(
+        res.headers()[header::CONTENT_DISPOSITION],
+        "attachment; filename=\"report.csv\""
+    );
+}
+
+#[test]
+fn attachment_escapes_quotes_and_control_characters() {
+    let res = (Attachment::new("a\"b\\c\r\n.txt"), "").into_response();
+    assert_eq!(
+        res.headers()[header::CONTENT_DISPOSITION],
+        "attachment; filename=\"a\\\"b\\\\c__.txt\""
+    );
+}
+
+#[test]
+fn attachment_encodes_non_ascii_filenames() {
+    let res = (Attachment::new("relatório 2024.csv"), "a,b").into_response();
+    assert_eq!(
+        res.headers()[header::CONTENT_DISPOSITION],
+        "attachment; filename=\"relat_rio 2024.csv\"; filename*=UTF-8''relat%C3%B3rio%202024.csv"
+    );
+}
+
+fn parse_id(id: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let id: u64 = id.parse()?;
+    Ok(format!("user {id}"))
+}
+
+#[test]
+fn boxed_errors_are_internal_server_errors() {
+    let router = Router::new()
+        .route("/users/:id", get(|Path(id): Path<String>| parse_id(&id)))
+        .error_details(true);
+
+    let res = TestRequestBuilder::get("/users/42").call(&router).unwrap();
+    assert_eq!(text(res), "user 42");
+
+    let res = TestRequestBuilder::get("/users/me").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(text(res), "invalid digit found in string");
+
+    let res = TestRequestBuilder::get("/users/me")
+        .call(&router.error_details(false))
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(text(res), "");
+}
+
+#[test]
+fn content_type_of_the_parts_wins_over_the_body_one() {
+    let res = ([(header::CONTENT_TYPE, "text/html")], "<b>hi</b>").into_response();
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "text/html");
+    assert_eq!(
+        res.headers().get_all(header::CONTENT_TYPE).iter().count(),
+        1
+    );
+    assert_eq!(text(res), "<b>hi</b>");
+}
+
+fn greet() -> Result<String, Infallible> {
+    Ok("hello".to_owned())
+}
+
+#[test]
+fn infallible_results_respond_with_the_ok_value() {
+    let router = Router::new()
+        .route("/greet", get(greet))
+        .route("/created", get(|| (StatusCode::CREATED, greet())));
+
+    let res = TestRequestBuilder::get("/greet").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(text(res), "hello");
+
+    let res = TestRequestBuilder::get("/created").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::CREATED);
+    assert_eq!(text(res), "hello");
+}
@@ -0,0 +1,662 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use spike::{
+    extract::{OriginalUri, Path},
+    http::{header, request::Parts as RequestParts, Method, Request, Response, StatusCode, Uri},
+    layer::HostFilter,
+    routing::{delete, get, post},
+    test::TestRequestBuilder,
+    Body, Router,
+};
+use touche::server::Service;
+
+mod common;
+
+use common::text;
+
+#[test]
+fn trailing_slash_redirect_comes_before_the_method_check() {
+    let router = Router::new()
+        .route("/users", get(|| "users"))
+        .redirect_trailing_slash(true);
+
+    let res = TestRequestBuilder::post("/users/?page=2")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(res.headers()[header::LOCATION], "/users?page=2");
+
+    let res = TestRequestBuilder::post("/users?page=2")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[test]
+fn trailing_slash_redirect_adds_the_missing_slash() {
+    let router = Router::new()
+        .route("/docs/", get(|| "docs"))
+        .redirect_trailing_slash(true);
+
+    let res = TestRequestBuilder::get("/docs").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(res.headers()[header::LOCATION], "/docs/");
+
+    let res = TestRequestBuilder::get("/docs/").call(&router).unwrap();
+    assert_eq!(text(res), "docs");
+}
+
+#[test]
+fn trailing_slash_redirect_stays_on_the_same_host() {
+    let router = Router::new()
+        .route("/:name", get(|| "name"))
+        .normalize_path(true)
+        .redirect_trailing_slash(true);
+
+    let res = TestRequestBuilder::get("//evil.com/")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(res.headers()[header::LOCATION], "/evil.com");
+
+    let res = TestRequestBuilder::get("///evil.com/")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.headers()[header::LOCATION], "/evil.com");
+}
+
+#[test]
+fn trailing_slash_redirect_is_disabled_by_default() {
+    let router = Router::new().route("/users", post(|| "created"));
+
+    let res = TestRequestBuilder::post("/users/").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn constraint_falls_through_to_not_found() {
+    let router = Router::new().route(
+        "/users/:id",
+        get(|| "user").constraint("id", |id| id.parse::<u64>().is_ok()),
+    );
+
+    let res = TestRequestBuilder::get("/users/42").call(&router).unwrap();
+    assert_eq!(text(res), "user");
+
+    let res = TestRequestBuilder::get("/users/me").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn constraint_sees_percent_decoded_values() {
+    let router = Router::new().route(
+        "/files/:name",
+        get(|Path(name): Path<String>| name).constraint("name", |name| !name.contains("..")),
+    );
+
+    let res = TestRequestBuilder::get("/files/a%20b")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "a b");
+
+    let res = TestRequestBuilder::get("/files/%2e%2e")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = TestRequestBuilder::get("/files/%ff").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+#[should_panic(expected = "constraint on `id`, which is not a param of route `/users/:user_id`")]
+fn constraint_on_unknown_param_panics() {
+    Router::new().route("/users/:user_id", get(|| "user").constraint("id", |_| true));
+}
+
+fn api_version(version: &'static str) -> impl Fn(&RequestParts) -> bool + Send + Sync {
+    move |parts| {
+        parts
+            .headers
+            .get("api-version")
+            .is_some_and(|v| v == version)
+    }
+}
+
+#[test]
+fn guards_select_the_version_by_a_header() {
+    let router = Router::new().route(
+        "/users",
+        get(|| "v1")
+            .guard(Method::GET, api_version("2"), || "v2")
+            .guard(Method::GET, api_version("3"), || "v3"),
+    );
+
+    let res = TestRequestBuilder::get("/users").call(&router).unwrap();
+    assert_eq!(text(res), "v1");
+
+    for version in ["2", "3"] {
+        let res = TestRequestBuilder::get("/users")
+            .header("api-version", version)
+            .call(&router)
+            .unwrap();
+        assert_eq!(text(res), format!("v{version}"));
+    }
+
+    let res = TestRequestBuilder::get("/users")
+        .header("api-version", "4")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "v1");
+}
+
+#[test]
+fn first_passing_guard_wins() {
+    let router = Router::new().route(
+        "/users",
+        get(|| "v1")
+            .guard(Method::GET, |_: &RequestParts| true, || "first")
+            .guard(Method::GET, |_: &RequestParts| true, || "second"),
+    );
+
+    let res = TestRequestBuilder::get("/users").call(&router).unwrap();
+    assert_eq!(text(res), "first");
+}
+
+#[test]
+fn guards_only_apply_to_their_method() {
+    let router = Router::new().route(
+        "/users",
+        get(|| "v1").guard(Method::GET, api_version("2"), || "v2"),
+    );
+
+    let res = TestRequestBuilder::delete("/users")
+        .header("api-version", "2")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+
+    let res = TestRequestBuilder::new(Method::HEAD, "/users")
+        .header("api-version", "2")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "v2");
+}
+
+#[test]
+fn layer_method_wraps_the_guards_of_the_method() {
+    let router = Router::new().route(
+        "/users",
+        get(|| "v1")
+            .post(|| "created")
+            .guard(Method::GET, api_version("2"), || "v2")
+            .layer_method(Method::GET, HostFilter::new(["example.com"])),
+    );
+
+    let res = TestRequestBuilder::get("/users")
+        .header("api-version", "2")
+        .header(header::HOST, "evil.com")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::MISDIRECTED_REQUEST);
+
+    let res = TestRequestBuilder::get("/users")
+        .header("api-version", "2")
+        .header(header::HOST, "example.com")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "v2");
+}
+
+#[test]
+fn disjoint_method_routers_merge() {
+    let mut users = get(|| "list");
+    users.merge(post(|| "created")).unwrap();
+    let router = Router::new().route("/users", users + delete(|| "deleted"));
+
+    let res = TestRequestBuilder::get("/users").call(&router).unwrap();
+    assert_eq!(text(res), "list");
+
+    let res = TestRequestBuilder::post("/users").call(&router).unwrap();
+    assert_eq!(text(res), "created");
+
+    let res = TestRequestBuilder::delete("/users").call(&router).unwrap();
+    assert_eq!(text(res), "deleted");
+}
+
+#[test]
+fn conflicting_merge_fails_without_changes() {
+    let mut users = get(|| "list");
+    let err = users.merge(get(|| "other").post(|| "created")).unwrap_err();
+    assert_eq!(err.methods(), ["GET"]);
+
+    let router = Router::new().route("/users", users);
+
+    let res = TestRequestBuilder::get("/users").call(&router).unwrap();
+    assert_eq!(text(res), "list");
+
+    let res = TestRequestBuilder::post("/users").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[test]
+#[should_panic(expected = "conflicting handlers for GET")]
+fn conflicting_add_panics() {
+    let _ = get(|| "list") + get(|| "other");
+}
+
+#[test]
+fn route_override_replaces_the_conflicting_methods() {
+    let default = Arc::new("default");
+    let captured = default.clone();
+    let router = Router::new()
+        .route("/users", get(move || *captured).post(|| "created"))
+        .route_override("/users", get(|| "override"));
+    assert_eq!(Arc::strong_count(&default), 1);
+
+    let res = TestRequestBuilder::get("/users").call(&router).unwrap();
+    assert_eq!(text(res), "override");
+
+    let res = TestRequestBuilder::post("/users").call(&router).unwrap();
+    assert_eq!(text(res), "created");
+}
+
+#[test]
+#[should_panic(expected = "conflicting handlers for GET on route `/users`")]
+fn route_without_override_panics_on_conflicts() {
+    Router::new()
+        .route("/users", get(|| "default"))
+        .route("/users", get(|| "override"));
+}
+
+#[test]
+fn options_lists_the_allowed_methods() {
+    let router = Router::new().route("/users", get(|| "list").post(|| "created"));
+
+    let res = TestRequestBuilder::new(Method::OPTIONS, "/users")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+    assert_eq!(res.headers()[header::ALLOW], "GET, HEAD, POST, OPTIONS");
+
+    let res = TestRequestBuilder::new(Method::OPTIONS, "/missing")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn options_handler_wins_over_the_automatic_response() {
+    let router = Router::new().route("/users", get(|| "list").options(|| "options"));
+
+    let res = TestRequestBuilder::new(Method::OPTIONS, "/users")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "options");
+}
+
+#[test]
+fn automatic_options_can_be_disabled() {
+    let router = Router::new()
+        .route("/users", get(|| "list").post(|| "created"))
+        .automatic_options(false);
+
+    let res = TestRequestBuilder::new(Method::OPTIONS, "/users")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[test]
+fn route_aliases_answer_on_every_path() {
+    let router = Router::new().route_aliases(&["/health", "/healthz"], get(|| "ok"));
+
+    for path in ["/health", "/healthz"] {
+        let res = TestRequestBuilder::get(path).call(&router).unwrap();
+        assert_eq!(text(res), "ok");
+    }
+}
+
+#[test]
+fn update_method_router_adds_a_method_to_a_route() {
+    let mut router = Router::new().route("/users/:id", get(|| "user"));
+
+    assert!(router.update_method_router("/users/:id", |route| route.delete(|| "deleted")));
+
+    let res = TestRequestBuilder::get("/users/1").call(&router).unwrap();
+    assert_eq!(text(res), "user");
+
+    let res = TestRequestBuilder::delete("/users/1")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "deleted");
+}
+
+#[test]
+fn update_method_router_is_false_for_unregistered_paths() {
+    let mut router = Router::new().route("/users/:id", get(|| "user"));
+
+    assert!(!router.update_method_router("/admin", |_| unreachable!()));
+    assert!(!router.update_method_router("/users/1", |_| unreachable!()));
+}
+
+#[test]
+fn update_method_router_validates_constraints() {
+    let mut router = Router::new().route("/users/:id", get(|Path(id): Path<String>| id));
+
+    router.update_method_router("/users/:id", |route| {
+        route.constraint("id", |id| id.parse::<u64>().is_ok())
+    });
+    let res = TestRequestBuilder::get("/users/1").call(&router).unwrap();
+    assert_eq!(text(res), "1");
+    let res = TestRequestBuilder::get("/users/me").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+#[should_panic(expected = "constraint on `user_id`, which is not a param of route `/users/:id`")]
+fn update_method_router_panics_on_constraints_of_unknown_params() {
+    let mut router = Router::new().route("/users/:id", get(|| "user"));
+
+    router.update_method_router("/users/:id", |route| {
+        route.constraint("user_id", |id| id.parse::<u64>().is_ok())
+    });
+}
+
+#[test]
+fn param_names_lists_the_params_in_extraction_order() {
+    let router = Router::new()
+        .route("/users/:id/posts/*rest", get(|| "posts"))
+        .route("/health", get(|| "ok"));
+
+    assert_eq!(
+        router.param_names("/users/:id/posts/*rest"),
+        Some(vec!["id", "rest"])
+    );
+    assert_eq!(router.param_names("/health"), Some(vec![]));
+    assert_eq!(router.param_names("/users/1/posts/a"), None);
+}
+
+#[test]
+fn param_names_match_the_path_extractor_arity() {
+    let router = Router::new().route(
+        "/users/:id/posts/:slug",
+        get(|Path((id, slug)): Path<(u32, String)>| format!("{id} {slug}")),
+    );
+    assert_eq!(
+        router
+            .param_names("/users/:id/posts/:slug")
+            .map(|names| names.len()),
+        Some(2)
+    );
+
+    let res = TestRequestBuilder::get("/users/1/posts/hello")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "1 hello");
+}
+
+#[derive(Clone)]
+struct Failing;
+
+impl Service for Failing {
+    type Body = Body;
+    type Error = io::Error;
+
+    fn call(&self, _req: Request<Body>) -> Result<Response<Body>, Self::Error> {
+        Err(io::Error::other("database is down"))
+    }
+}
+
+fn error_responses_router() -> Router {
+    Router::new()
+        .route("/users", get(|| "list"))
+        .route_service("/fail", Failing)
+        .default_error_response(StatusCode::NOT_FOUND, "nothing here")
+        .default_error_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            ([(header::CONTENT_TYPE, "text/html")], "<p>not allowed</p>"),
+        )
+        .default_error_response(StatusCode::INTERNAL_SERVER_ERROR, "try again later")
+}
+
+#[test]
+fn default_not_found_response_is_overridden() {
+    let res = TestRequestBuilder::get("/missing")
+        .call(&error_responses_router())
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(text(res), "nothing here");
+}
+
+#[test]
+fn default_method_not_allowed_response_is_overridden() {
+    let res = TestRequestBuilder::post("/users")
+        .call(&error_responses_router())
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "text/html");
+    assert_eq!(text(res), "<p>not allowed</p>");
+}
+
+#[test]
+fn default_internal_server_error_response_is_overridden() {
+    let res = TestRequestBuilder::get("/fail")
+        .call(&error_responses_router())
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(text(res), "try again later");
+}
+
+#[test]
+#[should_panic(expected = "400 Bad Request is not a default error response of the router")]
+fn default_error_response_for_other_statuses_panics() {
+    Router::new().default_error_response(StatusCode::BAD_REQUEST, "bad");
+}
+
+fn example_host_only() -> HostFilter {
+    HostFilter::new(["example.com"])
+}
+
+#[test]
+fn layer_method_leaves_the_other_methods_unaffected() {
+    let router = Router::new().route(
+        "/posts",
+        get(|| "posts")
+            .post(|| "created")
+            .layer_method(Method::POST, example_host_only())
+            .delete(|| "deleted"),
+    );
+
+    let res = TestRequestBuilder::post("/posts")
+        .header(header::HOST, "evil.com")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::MISDIRECTED_REQUEST);
+
+    for method in [Method::GET, Method::HEAD, Method::DELETE] {
+        let res = TestRequestBuilder::new(method, "/posts")
+            .header(header::HOST, "evil.com")
+            .call(&router)
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    let res = TestRequestBuilder::post("/posts")
+        .header(header::HOST, "example.com")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "created");
+}
+
+#[test]
+fn layer_method_on_get_also_wraps_head() {
+    let router = Router::new().route(
+        "/posts",
+        get(|| "posts").layer_method(Method::GET, example_host_only()),
+    );
+
+    let res = TestRequestBuilder::new(Method::HEAD, "/posts")
+        .header(header::HOST, "evil.com")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::MISDIRECTED_REQUEST);
+}
+
+#[test]
+#[should_panic(expected = "no POST handler to layer")]
+fn layer_method_without_a_handler_panics() {
+    let _ = get(|| "posts").layer_method(Method::POST, example_host_only());
+}
+
+fn nested(inner: Router, outer_not_found: Option<&'static str>) -> Router {
+    let outer = Router::new()
+        .route("/", get(|| "home"))
+        .nest_service("/api", inner.route("/users", get(|| "users")));
+    match outer_not_found {
+        Some(body) => outer.default_error_response(StatusCode::NOT_FOUND, body),
+        None => outer,
+    }
+}
+
+#[test]
+fn nested_not_found_response_wins() {
+    let inner = Router::new().default_error_response(StatusCode::NOT_FOUND, "api not found");
+    let router = nested(inner, Some("not found"));
+
+    let res = TestRequestBuilder::get("/api/missing")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(text(res), "api not found");
+
+    let res = TestRequestBuilder::get("/missing").call(&router).unwrap();
+    assert_eq!(text(res), "not found");
+
+    let res = TestRequestBuilder::get("/api/users").call(&router).unwrap();
+    assert_eq!(text(res), "users");
+}
+
+#[test]
+fn nested_router_falls_back_to_the_outer_not_found_response() {
+    let router = nested(Router::new(), Some("not found"));
+
+    let res = TestRequestBuilder::get("/api/missing")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(text(res), "not found");
+}
+
+#[test]
+fn nested_router_without_not_found_responses_gets_the_built_in_one() {
+    let router = nested(Router::new(), None);
+
+    let res = TestRequestBuilder::get("/api/missing")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(text(res), "");
+}
+
+#[test]
+fn head_resolves_the_params_of_the_get_handler_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counted = calls.clone();
+    let router = Router::new().route(
+        "/users/:id",
+        get(move |Path(id): Path<u32>| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            format!("user {id}")
+        }),
+    );
+
+    let res = TestRequestBuilder::new(Method::HEAD, "/users/42")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "user 42");
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    let res = TestRequestBuilder::new(Method::HEAD, "/users/me")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
+
+fn normalized_router() -> Router {
+    Router::new()
+        .route("/", get(|| "home"))
+        .route(
+            "/a/b",
+            get(|uri: Uri, OriginalUri(original): OriginalUri| format!("{uri} {original}")),
+        )
+        .route("/users/:id", get(|Path(id): Path<String>| id))
+        .normalize_path(true)
+}
+
+#[test]
+fn normalize_path_collapses_a_double_slash_root() {
+    let res = TestRequestBuilder::get("//")
+        .call(&normalized_router())
+        .unwrap();
+    assert_eq!(text(res), "home");
+}
+
+#[test]
+fn normalize_path_collapses_inner_slashes() {
+    let router = normalized_router();
+
+    let res = TestRequestBuilder::get("/a//b?x=1").call(&router).unwrap();
+    assert_eq!(text(res), "/a/b?x=1 /a//b?x=1");
+
+    let res = TestRequestBuilder::get("/users///42")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "42");
+}
+
+#[test]
+fn normalize_path_leaves_clean_paths_alone() {
+    let router = normalized_router();
+
+    let res = TestRequestBuilder::get("/a/b").call(&router).unwrap();
+    assert_eq!(text(res), "/a/b /a/b");
+
+    let res = TestRequestBuilder::get("/a/b/").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn paths_are_not_normalized_by_default() {
+    let router = Router::new().route("/a/b", get(|| "ab"));
+
+    let res = TestRequestBuilder::get("/a//b").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn nesting_on_the_root_keeps_the_inner_paths() {
+    let inner = Router::new().route("/foo", get(|uri: Uri| format!("foo {uri}")));
+    let router = Router::new()
+        .route("/bar", get(|| "bar"))
+        .nest_service("/", inner);
+
+    let res = TestRequestBuilder::get("/foo?x=1").call(&router).unwrap();
+    assert_eq!(text(res), "foo /foo?x=1");
+
+    let res = TestRequestBuilder::get("/bar").call(&router).unwrap();
+    assert_eq!(text(res), "bar");
+
+    let res = TestRequestBuilder::get("/missing").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = TestRequestBuilder::get("//foo").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,311 @@
+use std::{
+    io::{Read, Write},
+    iter,
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use spike::{
+    extract::BodyStream,
+    http::{Extensions, StatusCode},
+    layer::{HostFilter, Layer, LogFormat, Logger},
+    routing::{get, post, BodyPolicy},
+    Router, Server, ServerConfig,
+};
+
+// Serves the `router` on a random port of the loopback interface.
+fn serve(router: Router) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let connections = iter::from_fn(move || Some(listener.accept().ok()?.into()));
+    thread::spawn(move || {
+        Server::builder()
+            .from_connections(connections)
+            .serve(router)
+    });
+    addr
+}
+
+// Writes the `requests` on a single connection and reads until the server closes it, so the last
+// request should close the connection.
+fn exchange(addr: SocketAddr, requests: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    stream.write_all(requests.as_bytes()).unwrap();
+    let mut responses = Vec::new();
+    stream.read_to_end(&mut responses).ok();
+    String::from_utf8(responses).unwrap()
+}
+
+fn statuses(responses: &str) -> Vec<&str> {
+    responses
+        .match_indices("HTTP/1.1 ")
+        .map(|(start, _)| &responses[start + 9..start + 12])
+        .collect()
+}
+
+const CHUNKED_POST: &str = "Transfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+const LAST_GET: &str = "GET /ok HTTP/1.1\r\nConnection: close\r\n\r\n";
+
+fn router() -> Router {
+    Router::new()
+        .route("/ok", get(|| "ok"))
+        .route("/empty", post(|| StatusCode::NO_CONTENT))
+        .route("/echo", post(|body: String| body))
+        .route(
+            "/peek",
+            post(|mut body: BodyStream| {
+                let mut start = [0; 2];
+                body.read_exact(&mut start).unwrap();
+                start.to_vec()
+            }),
+        )
+        .route(
+            "/extensions",
+            post(|extensions: Extensions| extensions.len().to_string()),
+        )
+        .route("/parts", post(|_: HeaderRequired| StatusCode::ACCEPTED))
+        .route(
+            "/header",
+            post(|_: spike::http::HeaderMap, _: HeaderRequired, body: String| body),
+        )
+}
+
+struct HeaderRequired;
+
+impl spike::extract::FromRequestPart for HeaderRequired {
+    type Rejection = StatusCode;
+
+    fn from_request_parts(
+        parts: &mut spike::http::request::Parts,
+    ) -> Result<Self, Self::Rejection> {
+        match parts.headers.contains_key("x-required") {
+            true => Ok(HeaderRequired),
+            false => Err(StatusCode::BAD_REQUEST),
+        }
+    }
+}
+
+#[test]
+fn pipelined_request_after_a_chunked_body_rejected_by_an_extractor() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!("POST /header HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["400", "200"]);
+}
+
+#[test]
+fn pipelined_request_after_a_chunked_body_ignored_by_the_handler() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!("POST /empty HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["204", "200"]);
+}
+
+#[test]
+fn pipelined_request_after_a_chunked_body_not_extracted() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!("POST /parts HTTP/1.1\r\nX-Required: 1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["202", "200"]);
+}
+
+#[test]
+fn pipelined_request_after_a_chunked_body_without_route() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!("POST /missing HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["404", "200"]);
+}
+
+#[test]
+fn pipelined_request_after_a_chunked_body_with_method_not_allowed() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!("POST /ok HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["405", "200"]);
+}
+
+#[test]
+fn chunked_body_rejected_by_a_before_layer_closes_the_connection() {
+    let addr = serve(router().before(HostFilter::new(["example.com"])));
+    let responses = exchange(
+        addr,
+        &format!("POST /ok HTTP/1.1\r\nHost: evil.com\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["421"]);
+    assert!(responses.to_ascii_lowercase().contains("connection: close"));
+}
+
+#[test]
+fn pipelined_request_after_an_ignored_chunked_body() {
+    let addr = serve(router().body_policy(BodyPolicy::Ignore));
+    let responses = exchange(
+        addr,
+        &format!("GET /ok HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["200", "200"]);
+}
+
+#[test]
+fn pipelined_request_after_a_rejected_chunked_body() {
+    let addr = serve(router().body_policy(BodyPolicy::Reject));
+    let responses = exchange(
+        addr,
+        &format!("GET /ok HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["400", "200"]);
+}
+
+#[test]
+fn ignored_chunked_body_past_the_drain_limit_closes_the_connection() {
+    let chunk = "x".repeat(64 * 1024);
+    let addr = serve(router().body_policy(BodyPolicy::Ignore));
+    let responses = exchange(
+        addr,
+        &format!(
+            "GET /ok HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{chunk}\r\n1\r\nx\r\n0\r\n\r\n{LAST_GET}",
+            chunk.len()
+        ),
+    );
+    assert_eq!(statuses(&responses), ["200"]);
+    assert!(responses.to_ascii_lowercase().contains("connection: close"));
+}
+
+#[test]
+fn head_response_has_the_get_length_without_the_body() {
+    let addr = serve(router());
+    let responses = exchange(addr, &format!("HEAD /ok HTTP/1.1\r\n\r\n{LAST_GET}"));
+    assert_eq!(statuses(&responses), ["200", "200"]);
+
+    let (head, get) = responses.split_at(responses.rfind("HTTP/1.1 ").unwrap());
+    assert!(head.to_ascii_lowercase().contains("content-length: 2\r\n"));
+    assert!(head.ends_with("\r\n\r\n"));
+    assert!(get.ends_with("\r\n\r\nok"));
+}
+
+#[test]
+fn logger_logs_the_client_host_and_written_size() {
+    let sink = Arc::new(Mutex::new(Vec::new()));
+    let svc = Logger::new(sink.clone())
+        .format(LogFormat::Common)
+        .layer(router())
+        .into_make_service_with_connect_info();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let connections = iter::from_fn(move || Some(listener.accept().ok()?.into()));
+    thread::spawn(move || {
+        Server::builder()
+            .from_connections(connections)
+            .make_service(svc)
+    });
+
+    let responses = exchange(addr, &format!("HEAD /ok HTTP/1.1\r\n\r\n{LAST_GET}"));
+    assert_eq!(statuses(&responses), ["200", "200"]);
+
+    let log = String::from_utf8(sink.lock().unwrap().clone()).unwrap();
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("127.0.0.1 - - ["));
+    assert!(lines[0].ends_with("\"HEAD /ok HTTP/1.1\" 200 -"));
+    assert!(lines[1].ends_with("\"GET /ok HTTP/1.1\" 200 2"));
+}
+
+#[test]
+fn server_config_serves_a_listener_bound_to_a_random_port() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || ServerConfig::new().serve_listener(listener, router()));
+
+    let responses = exchange(addr, LAST_GET);
+    assert_eq!(statuses(&responses), ["200"]);
+}
+
+#[test]
+fn serves_a_bare_method_router() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let connections = iter::from_fn(move || Some(listener.accept().ok()?.into()));
+    thread::spawn(move || {
+        Server::builder()
+            .from_connections(connections)
+            .serve(get(|| "ok"))
+    });
+
+    let responses = exchange(
+        addr,
+        "POST /any/path HTTP/1.1\r\nContent-Length: 0\r\n\r\nGET /other HTTP/1.1\r\nConnection: close\r\n\r\n",
+    );
+    assert_eq!(statuses(&responses), ["405", "200"]);
+    assert!(responses.ends_with("\r\n\r\nok"));
+}
+
+#[test]
+fn string_extractor_reads_chunked_bodies_whole() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!(
+            "POST /echo HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n7\r\n, world\r\n0\r\n\r\n{LAST_GET}"
+        ),
+    );
+    assert_eq!(statuses(&responses), ["200", "200"]);
+
+    let (echo, _) = responses.split_at(responses.rfind("HTTP/1.1 ").unwrap());
+    assert!(echo.to_ascii_lowercase().contains("content-length: 12\r\n"));
+    assert!(echo.ends_with("\r\n\r\nhello, world"));
+}
+
+#[test]
+fn pipelined_request_after_a_chunked_body_with_an_extensions_extractor() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!("POST /extensions HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["200", "200"]);
+}
+
+#[test]
+fn pipelined_request_after_a_partially_read_chunked_body() {
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!("POST /peek HTTP/1.1\r\n{CHUNKED_POST}{LAST_GET}"),
+    );
+    assert_eq!(statuses(&responses), ["200", "200"]);
+
+    let (peek, _) = responses.split_at(responses.rfind("HTTP/1.1 ").unwrap());
+    assert!(peek.ends_with("\r\n\r\nhe"));
+}
+
+#[test]
+fn partially_read_chunked_body_past_the_drain_limit_closes_the_connection() {
+    let chunk = "x".repeat(64 * 1024);
+    let addr = serve(router());
+    let responses = exchange(
+        addr,
+        &format!(
+            "POST /peek HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{chunk}\r\n{:x}\r\n{chunk}\r\n0\r\n\r\n{LAST_GET}",
+            chunk.len(),
+            chunk.len()
+        ),
+    );
+    assert_eq!(statuses(&responses), ["200"]);
+    assert!(responses.to_ascii_lowercase().contains("connection: close"));
+}
@@ -0,0 +1,200 @@
+use std::{
+    fs::{self, File},
+    path::PathBuf,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use spike::{
+    http::{header, StatusCode},
+    services::{ServeDir, ServeFile},
+    test::TestRequestBuilder,
+    Router,
+};
+
+mod common;
+
+use common::text;
+
+// Sun, 06 Nov 1994 08:49:37 GMT
+const NOV_1994: u64 = 784_111_777;
+
+// Creates a fresh directory for the test, with an `app.css` modified on `NOV_1994`.
+fn public_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("spike-{}-{test}", std::process::id()));
+    fs::create_dir_all(dir.join("css")).unwrap();
+    fs::write(dir.join("css/app.css"), "body {}").unwrap();
+    File::options()
+        .write(true)
+        .open(dir.join("css/app.css"))
+        .unwrap()
+        .set_modified(UNIX_EPOCH + Duration::from_secs(NOV_1994))
+        .unwrap();
+    dir
+}
+
+#[test]
+fn serves_the_file_with_its_modification_time() {
+    let dir = public_dir("modification-time");
+    let router = Router::new().route_service("/app.css", ServeFile::new(dir.join("css/app.css")));
+
+    let res = TestRequestBuilder::get("/app.css").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "text/css;charset=utf-8"
+    );
+    assert_eq!(
+        res.headers()[header::LAST_MODIFIED],
+        "Sun, 06 Nov 1994 08:49:37 GMT"
+    );
+    assert_eq!(text(res), "body {}");
+}
+
+#[test]
+fn repeat_request_gets_not_modified() {
+    let dir = public_dir("repeat-request");
+    let router = Router::new().route_service("/app.css", ServeFile::new(dir.join("css/app.css")));
+
+    let res = TestRequestBuilder::get("/app.css").call(&router).unwrap();
+    let last_modified = res.headers()[header::LAST_MODIFIED].clone();
+
+    let res = TestRequestBuilder::get("/app.css")
+        .header(header::IF_MODIFIED_SINCE, last_modified.clone())
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(res.headers()[header::LAST_MODIFIED], last_modified);
+    assert_eq!(text(res), "");
+
+    let res = TestRequestBuilder::get("/app.css")
+        .header(header::IF_MODIFIED_SINCE, "Mon, 07 Nov 1994 00:00:00 GMT")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+}
+
+#[test]
+fn modified_file_is_served_again() {
+    let dir = public_dir("modified");
+    let router = Router::new().route_service("/app.css", ServeFile::new(dir.join("css/app.css")));
+
+    let res = TestRequestBuilder::get("/app.css")
+        .header(header::IF_MODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:36 GMT")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(text(res), "body {}");
+
+    let res = TestRequestBuilder::get("/app.css")
+        .header(header::IF_MODIFIED_SINCE, "yesterday")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test]
+fn modification_time_out_of_range_is_left_out() {
+    let dir = public_dir("out-of-range");
+    let path = dir.join("css/app.css");
+    File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(UNIX_EPOCH - Duration::from_secs(86400))
+        .unwrap();
+    let router = Router::new().route_service("/app.css", ServeFile::new(path));
+
+    let res = TestRequestBuilder::get("/app.css")
+        .header(header::IF_MODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!res.headers().contains_key(header::LAST_MODIFIED));
+    assert_eq!(text(res), "body {}");
+}
+
+#[test]
+fn only_get_and_head_are_allowed() {
+    let dir = public_dir("methods");
+    let router = Router::new().route_service("/app.css", ServeFile::new(dir.join("css/app.css")));
+
+    let res = TestRequestBuilder::post("/app.css").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(res.headers()[header::ALLOW], "GET, HEAD");
+}
+
+#[test]
+fn serves_the_files_under_the_directory() {
+    let dir = public_dir("directory");
+    let router = Router::new().nest_service("/static", ServeDir::new(&dir));
+
+    let res = TestRequestBuilder::get("/static/css/app.css")
+        .call(&router)
+        .unwrap();
+    assert_eq!(text(res), "body {}");
+
+    let res = TestRequestBuilder::get("/static/css/app.css")
+        .header(header::IF_MODIFIED_SINCE, "Sun, 06 Nov 1994 08:49:37 GMT")
+        .call(&router)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+    for path in ["/static/css", "/static/css/missing.css"] {
+        let res = TestRequestBuilder::get(path).call(&router).unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+}
+
+#[test]
+fn paths_escaping_the_directory_are_not_found() {
+    let dir = public_dir("escape");
+    fs::write(dir.join("secret"), "secret").unwrap();
+    let router = Router::new().nest_service("/static", ServeDir::new(dir.join("css")));
+
+    for path in [
+        "/static/../secret",
+        "/static/%2e%2e/secret",
+        "/static/%2E%2E%2Fsecret",
+        "/static/..%5csecret",
+        "/static/%2fsecret",
+        "/static/app.css%00",
+        "/static/a/../../secret",
+        "/static/%2e%2e%2f%2e%2e%2fsecret",
+        "/static/.%2e/secret",
+        "/static/%5C..%5Csecret",
+        "/static/..\\secret",
+        "/static/C:%5Csecret",
+        "/static/%ff",
+    ] {
+        let res = TestRequestBuilder::get(path).call(&router).unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+}
+
+#[test]
+fn absolute_paths_stay_under_the_directory() {
+    let dir = public_dir("absolute");
+    let secret = dir.join("secret");
+    fs::write(&secret, "secret").unwrap();
+    let router = Router::new().nest_service("/static", ServeDir::new(dir.join("css")));
+
+    let absolute = secret.to_str().unwrap().replace('/', "%2F");
+    for path in [
+        format!("/static/{absolute}"),
+        format!("/static//{}", secret.display()),
+    ] {
+        let res = TestRequestBuilder::get(&path).call(&router).unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND, "{path}");
+    }
+
+    // Without the prefix, the service sees the whole path, which is still relative to the root.
+    let res = TestRequestBuilder::get(&format!("/{}", dir.join("css/app.css").display()))
+        .call(&ServeDir::new(&dir))
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = TestRequestBuilder::get("/css/./app.css")
+        .call(&ServeDir::new(&dir))
+        .unwrap();
+    assert_eq!(text(res), "body {}");
+}
@@ -0,0 +1,154 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use spike::{routing::get, Router, ServerConfig};
+
+struct Server {
+    addr: SocketAddr,
+    // Sends the shutdown signal.
+    shutdown: Sender<()>,
+    // Receives the result of the server, once it returns.
+    done: Receiver<io::Result<()>>,
+}
+
+// Serves `/ok`, and `/slow`, whose handler signals `started` when called, then blocks until
+// released by a message on `release`, with a grace period long enough to never be reached.
+fn serve(started: Sender<()>, release: Receiver<()>) -> Server {
+    let started = Arc::new(Mutex::new(started));
+    let release = Arc::new(Mutex::new(release));
+    let router = Router::new().route("/ok", get(|| "ok")).route(
+        "/slow",
+        get(move || {
+            started.lock().unwrap().send(()).ok();
+            release.lock().unwrap().recv().ok();
+            "slow"
+        }),
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown, signal) = mpsc::channel();
+    let (finished, done) = mpsc::channel();
+    thread::spawn(move || {
+        let res = ServerConfig::new()
+            .shutdown_grace_period(Duration::from_secs(60))
+            .serve_listener_with_graceful_shutdown(listener, router, move || {
+                signal.recv().ok();
+            });
+        finished.send(res).ok();
+    });
+
+    Server {
+        addr,
+        shutdown,
+        done,
+    }
+}
+
+fn connect(addr: SocketAddr) -> TcpStream {
+    let stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream
+}
+
+// Reads a response whose body is `body`, leaving the connection open.
+fn read_response(stream: &mut TcpStream, body: &str) -> String {
+    let mut res = Vec::new();
+    let mut buf = [0; 1024];
+    while !res.ends_with(format!("\r\n\r\n{body}").as_bytes()) {
+        let n = stream.read(&mut buf).unwrap();
+        assert!(n > 0, "closed before the response");
+        res.extend_from_slice(&buf[..n]);
+    }
+    String::from_utf8(res).unwrap()
+}
+
+// Whether the server closed the connection, rather than leaving it open past the read timeout.
+fn is_closed(stream: &mut TcpStream) -> bool {
+    let mut rest = Vec::new();
+    match stream.read_to_end(&mut rest) {
+        Ok(_) => true,
+        Err(err) => !matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut),
+    }
+}
+
+#[test]
+fn closes_idle_connections_and_busy_ones_after_their_response() {
+    let (started, calls) = mpsc::channel();
+    let (release, released) = mpsc::channel();
+    let server = serve(started, released);
+
+    let mut idle = connect(server.addr);
+    idle.write_all(b"GET /ok HTTP/1.1\r\n\r\n").unwrap();
+    read_response(&mut idle, "ok");
+
+    let mut busy = connect(server.addr);
+    busy.write_all(b"GET /slow HTTP/1.1\r\n\r\n").unwrap();
+    calls.recv().unwrap();
+
+    server.shutdown.send(()).unwrap();
+    assert!(is_closed(&mut idle));
+
+    release.send(()).unwrap();
+    let res = read_response(&mut busy, "slow");
+    assert!(res.contains("connection: close\r\n"), "{res}");
+    assert!(is_closed(&mut busy));
+
+    server.done.recv().unwrap().unwrap();
+}
+
+#[test]
+fn closes_connections_made_while_shutting_down() {
+    let (started, calls) = mpsc::channel();
+    let (release, released) = mpsc::channel();
+    let server = serve(started, released);
+
+    // Keeps the server from returning while the connections below are made.
+    let mut busy = connect(server.addr);
+    busy.write_all(b"GET /slow HTTP/1.1\r\n\r\n").unwrap();
+    calls.recv().unwrap();
+
+    // Connects idle clients before, while and after the shutdown begins.
+    let stop = Arc::new(AtomicBool::new(false));
+    let connecting = thread::spawn({
+        let stop = stop.clone();
+        let addr = server.addr;
+        move || {
+            let mut clients = Vec::new();
+            while !stop.load(Ordering::SeqCst) && clients.len() < 200 {
+                match TcpStream::connect(addr) {
+                    Ok(stream) => clients.push(stream),
+                    Err(_) => break,
+                }
+            }
+            clients
+        }
+    });
+
+    server.shutdown.send(()).unwrap();
+    release.send(()).unwrap();
+    read_response(&mut busy, "slow");
+
+    // Idle connections would keep the server open for the whole grace period.
+    let res = server.done.recv_timeout(Duration::from_secs(10));
+    stop.store(true, Ordering::SeqCst);
+    res.expect("the server kept connections open").unwrap();
+
+    for mut client in connecting.join().unwrap() {
+        client
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        assert!(is_closed(&mut client));
+    }
+}
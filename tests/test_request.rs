@@ -0,0 +1,65 @@
+use spike::{
+    extract::Extension,
+    http::{header, HeaderMap, StatusCode},
+    layer::{HostFilter, Layer},
+    routing::{get, post},
+    test::TestRequestBuilder,
+    Router,
+};
+
+mod common;
+
+use common::text;
+
+#[derive(Clone)]
+struct User {
+    name: &'static str,
+}
+
+fn router() -> Router {
+    Router::new()
+        .route(
+            "/me",
+            get(|Extension(user): Extension<User>| user.name.to_owned()),
+        )
+        .route(
+            "/echo",
+            post(|headers: HeaderMap, body: String| {
+                format!("{} {body}", headers["x-name"].to_str().unwrap())
+            }),
+        )
+}
+
+#[test]
+fn inserts_extensions_on_the_request() {
+    let res = TestRequestBuilder::get("/me")
+        .extension(User { name: "rust" })
+        .call(&router())
+        .unwrap();
+    assert_eq!(text(res), "rust");
+
+    let res = TestRequestBuilder::get("/me").call(&router()).unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[test]
+fn sends_the_headers_and_body() {
+    let res = TestRequestBuilder::post("/echo")
+        .header("x-name", "rust")
+        .body("hello")
+        .call(&router())
+        .unwrap();
+    assert_eq!(text(res), "rust hello");
+}
+
+#[test]
+fn calls_layered_services() {
+    let guarded = HostFilter::new(["example.com"]).layer(router());
+
+    let res = TestRequestBuilder::get("/me")
+        .header(header::HOST, "evil.com")
+        .extension(User { name: "rust" })
+        .call(&guarded)
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::MISDIRECTED_REQUEST);
+}
@@ -0,0 +1,103 @@
+use std::{
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use spike::{
+    http::{header, StatusCode},
+    layer::Timeout,
+    routing::get,
+    test::TestRequestBuilder,
+    Router,
+};
+
+// A `/slow` route whose handler signals `started` when called, then blocks until released by a
+// message on the sender returned.
+fn slow_router(timeout: Timeout, started: Sender<()>) -> (Router, Sender<()>) {
+    let (release, released) = mpsc::channel();
+    let released = Arc::new(Mutex::new(released));
+    let started = Arc::new(Mutex::new(started));
+    let router = Router::new()
+        .route(
+            "/slow",
+            get(move || {
+                started.lock().unwrap().send(()).ok();
+                released.lock().unwrap().recv().ok();
+                "slow"
+            }),
+        )
+        .before(timeout);
+    (router, release)
+}
+
+#[test]
+fn handler_blocking_past_the_timeout_gets_service_unavailable() {
+    let (started, calls) = mpsc::channel();
+    let (router, release) = slow_router(Timeout::new(Duration::from_millis(10)), started);
+
+    let res = TestRequestBuilder::get("/slow").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.headers()[header::CONNECTION], "close");
+
+    // The handler was called, and is still blocked.
+    calls.recv().unwrap();
+    release.send(()).unwrap();
+}
+
+#[test]
+fn handler_within_the_timeout_responds() {
+    let router = Router::new()
+        .route("/fast", get(|| "fast"))
+        .before(Timeout::new(Duration::from_secs(5)));
+
+    let res = TestRequestBuilder::get("/fast").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!res.headers().contains_key(header::CONNECTION));
+}
+
+#[test]
+fn panicking_handler_gets_internal_server_error() {
+    let router = Router::new()
+        .route(
+            "/panic",
+            get(|| -> &'static str { panic!("handler panic") }),
+        )
+        .before(Timeout::new(Duration::from_secs(5)));
+
+    let res = TestRequestBuilder::get("/panic").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(res.headers()[header::CONNECTION], "close");
+}
+
+#[test]
+fn abandoned_threads_count_against_the_limit() {
+    let (started, calls) = mpsc::channel();
+    let timeout = Timeout::new(Duration::from_millis(10)).max_threads(1);
+    let (router, release) = slow_router(timeout, started);
+
+    let res = TestRequestBuilder::get("/slow").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    calls.recv().unwrap();
+
+    // The first handler is still blocked, so this one isn't called at all.
+    let res = TestRequestBuilder::get("/slow").call(&router).unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(res.headers()[header::CONNECTION], "close");
+    assert!(calls.try_recv().is_err());
+
+    // Once released, its thread frees the slot right after returning, so this polls until the
+    // handler is called again.
+    release.send(()).unwrap();
+    release.send(()).unwrap();
+    let mut tries = 0;
+    while calls.try_recv().is_err() {
+        tries += 1;
+        assert!(tries < 5000, "the slot was never freed");
+        thread::sleep(Duration::from_millis(1));
+        TestRequestBuilder::get("/slow").call(&router).unwrap();
+    }
+}